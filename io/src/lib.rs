@@ -1,13 +1,15 @@
 #![no_std]
 
 use gmeta::{In, InOut, Metadata};
-use gstd::{prelude::*, ActorId};
+use gstd::{prelude::*, ActorId, MessageId};
 use primitive_types::U256;
 
 pub type ContractId = ActorId;
 pub type TokenId = U256;
 pub type Price = u128;
 pub type TransactionId = u64;
+pub type BundleId = u64;
+pub type SwapId = u64;
 
 pub struct MarketMetadata;
 
@@ -23,14 +25,69 @@ impl Metadata for MarketMetadata {
 #[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
 pub struct Market {
     pub admin_id: ActorId,
+    /// the account proposed as the new admin that must accept the role
+    pub pending_admin_id: Option<ActorId>,
+    /// the accounts that have the roles besides the admin
+    pub roles: BTreeMap<Role, BTreeSet<ActorId>>,
     pub treasury_id: ActorId,
+    /// the treasury fee in basis points (1/100 of a percent)
     pub treasury_fee: u16,
+    /// the treasury fee that replaces the current one after the timelock
+    pub scheduled_treasury_fee: Option<ScheduledTreasuryFee>,
+    /// the treasury fees of the NFT contracts that override the marketplace fee
+    pub collection_fees: BTreeMap<ContractId, u16>,
+    /// the treasury fees of the NFT contracts that replace the current ones after the timelock
+    pub scheduled_collection_fees: BTreeMap<ContractId, ScheduledCollectionFee>,
     pub items: BTreeMap<(ContractId, TokenId), Item>,
+    pub collection_offers: BTreeMap<ContractId, CollectionOffers>,
+    pub bundles: BTreeMap<BundleId, Bundle>,
+    pub bundle_id: BundleId,
+    pub swaps: BTreeMap<SwapId, Swap>,
+    pub swap_id: SwapId,
     pub approved_nft_contracts: BTreeSet<ActorId>,
     pub approved_ft_contracts: BTreeSet<ActorId>,
+    /// whether the trading is stopped on the whole marketplace
+    pub is_paused: bool,
+    /// the NFT contracts whose NFTs can't be traded
+    pub paused_nft_contracts: BTreeSet<ContractId>,
+    /// the block height and timestamp of the marketplace initialization
+    /// (used to estimate the block duration)
+    pub initialized_at: (u32, u64),
     pub tx_id: TransactionId,
 }
 
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone, Copy)]
+pub struct ScheduledTreasuryFee {
+    /// the new treasury fee in basis points
+    pub fee: u16,
+    /// the time after which the new fee is applied to the sales
+    pub effective_at: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone, Copy)]
+pub struct ScheduledCollectionFee {
+    /// the new fee in basis points or `None` to charge the marketplace fee
+    pub fee: Option<u16>,
+    /// the time after which the new fee is applied to the sales
+    pub effective_at: u64,
+}
+
+/// The role that grants the right to make the admin actions.
+/// The admin and the accounts that have the [`Role::Admin`] role have all roles.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, TypeInfo, Clone, Copy)]
+pub enum Role {
+    /// Grants and revokes roles.
+    Admin,
+    /// Adds and removes NFT and FT contracts.
+    ContractCurator,
+    /// Manages the treasury fee and the treasury account.
+    FeeManager,
+    /// Pauses the marketplace.
+    Pauser,
+    /// Cancels auctions with bids.
+    Moderator,
+}
+
 #[derive(Debug, Encode, Decode, TypeInfo)]
 pub struct ItemInfoArgs {
     nft_contract_id: ActorId,
@@ -41,6 +98,8 @@ pub struct ItemInfoArgs {
 pub struct InitMarket {
     pub admin_id: ActorId,
     pub treasury_id: ActorId,
+    /// the treasury fee in basis points (300 is 3%), it can't be zero or greater than 5%.
+    /// It used to be set in percent, so the old configs must be multiplied by 100.
     pub treasury_fee: u16,
 }
 
@@ -49,8 +108,79 @@ pub struct Auction {
     pub bid_period: u64,
     pub started_at: u64,
     pub ended_at: u64,
+    pub max_ended_at: u64,
     pub current_price: Price,
     pub current_winner: ActorId,
+    /// the commitment to the hidden reserve price (see [`MarketAction::CreateAuction`])
+    pub reserve_price_commitment: Option<[u8; 32]>,
+    /// the reserve price revealed by the owner
+    pub reserve_price: Option<Price>,
+    pub buy_now_price: Option<Price>,
+    pub bid_increment: BidIncrement,
+    pub auto_settlement: Option<AutoSettlement>,
+}
+
+/// The automatic settlement of the auction scheduled by the delayed message to the marketplace.
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct AutoSettlement {
+    /// the delayed [`MarketAction::SettleAuction`] message
+    pub message_id: MessageId,
+    /// the block at which the message is dispatched
+    pub block: u32,
+    /// the gas reserved for the settlement
+    pub reserved_gas: u64,
+}
+
+/// The minimum amount by which a new bid must exceed the current one.
+/// If both parts are set, the larger of them is required.
+#[derive(Debug, PartialEq, Eq, Default, Encode, Decode, TypeInfo, Clone, Copy)]
+pub struct BidIncrement {
+    /// the absolute increment
+    pub absolute: Price,
+    /// the increment relative to the current price in basis points
+    pub basis_points: u16,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub enum PriceDecay {
+    /// The price decreases continuously from the start price to the floor price.
+    Linear,
+    /// The price decreases by equal steps every `step` milliseconds.
+    Stepwise { step: u64 },
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct DutchAuction {
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub start_price: Price,
+    pub floor_price: Price,
+    pub decay: PriceDecay,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone, Copy)]
+pub enum SealedBidPricing {
+    /// The winner pays the price of the winning bid.
+    FirstPrice,
+    /// The winner pays the price of the second highest bid (or the minimum price).
+    SecondPrice,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct SealedBid {
+    pub commitment: [u8; 32],
+    pub deposit: Price,
+    pub revealed_price: Option<Price>,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct SealedAuction {
+    pub min_price: Price,
+    pub pricing: SealedBidPricing,
+    pub started_at: u64,
+    pub commit_ended_at: u64,
+    pub reveal_ended_at: u64,
+    pub bids: BTreeMap<ActorId, SealedBid>,
 }
 
 #[derive(Debug, Encode, Decode, TypeInfo, Clone, PartialEq, Eq)]
@@ -60,21 +190,160 @@ pub enum MarketTx {
         account: ActorId,
         price: Price,
     },
-    SettleAuction,
+    SettleAuction {
+        /// the treasury account and fee at the start of the transaction
+        treasury: (ActorId, u16),
+    },
+    CancelAuction,
     Sale {
         buyer: ActorId,
+        price: Price,
+        /// the treasury account and fee at the start of the transaction
+        treasury: (ActorId, u16),
     },
     Offer {
         ft_id: ContractId,
         price: Price,
         account: ActorId,
+        expires_at: Option<u64>,
+        /// the attributes of the collection offer
+        attributes: Attributes,
+    },
+    AcceptOffer {
+        offer_id: OfferId,
+        /// the treasury account and fee at the start of the transaction
+        treasury: (ActorId, u16),
+    },
+    AcceptCounterOffer {
+        offer_id: OfferId,
+        counter_price: Price,
+        /// the treasury account and fee at the start of the transaction
+        treasury: (ActorId, u16),
+    },
+    AcceptCollectionOffer {
+        offer_id: OfferId,
+        offer: Offer,
+        /// the treasury account and fee at the start of the transaction
+        treasury: (ActorId, u16),
     },
-    AcceptOffer,
     Withdraw {
         ft_id: ContractId,
         price: Price,
         account: ActorId,
     },
+    CreateSealedAuction,
+    SealedBid {
+        account: ActorId,
+        commitment: [u8; 32],
+        deposit: Price,
+    },
+    SettleSealedAuction {
+        /// the treasury account and fee at the start of the transaction
+        treasury: (ActorId, u16),
+    },
+    BundleSale {
+        buyer: ActorId,
+        price: Price,
+        progress: TradeProgress,
+        /// the treasury accounts and fees of the bundle NFT contracts at the start of the transaction
+        treasuries: BTreeMap<ContractId, (ActorId, u16)>,
+    },
+    Swap {
+        progress: TradeProgress,
+    },
+}
+
+/// The step of the bundle sale or swap at which the transaction stopped.
+///
+/// The NFTs are first moved to the marketplace account, so the trade can be reverted
+/// if one of them can't be transferred or the buyer can't pay.
+#[derive(Debug, Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq)]
+pub enum TradeProgress {
+    /// the number of NFTs moved to the marketplace account
+    Locked(u32),
+    /// the trade is reverted and the locked NFTs are being returned to their owners
+    Reverted { locked: u32, returned: u32 },
+    /// the buyer has paid, the payouts are being sent
+    Paid,
+    /// the number of NFTs transferred to their new owners
+    Delivered(u32),
+}
+
+/// The set of NFTs sold together for a single price.
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct Bundle {
+    pub owner: ActorId,
+    pub tokens: Vec<(ContractId, TokenId)>,
+    pub ft_contract_id: Option<ContractId>,
+    pub price: Price,
+    pub tx: Option<(TransactionId, MarketTx)>,
+}
+
+/// The proposal to exchange NFTs of the proposer for NFTs of the counterparty.
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct Swap {
+    pub proposer: ActorId,
+    pub counterparty: ActorId,
+    /// the NFTs of the proposer
+    pub offered: Vec<(ContractId, TokenId)>,
+    /// the NFTs of the counterparty
+    pub requested: Vec<(ContractId, TokenId)>,
+    /// the fungible token contract address of the additional payment
+    /// (If it is `None` then the native value is held by the marketplace until the swap is over)
+    pub ft_contract_id: Option<ContractId>,
+    /// the additional payment from the proposer to the counterparty
+    pub payment: Price,
+    pub tx: Option<(TransactionId, MarketTx)>,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct Offer {
+    pub expires_at: Option<u64>,
+    pub counter_offer: Option<CounterOffer>,
+    /// the attributes the token must have to accept the collection offer
+    /// (empty for the item offers and the collection offers for any token)
+    pub attributes: Attributes,
+}
+
+/// The price proposed by the NFT owner to the offer creator instead of the offered one.
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct CounterOffer {
+    pub owner: ActorId,
+    pub price: Price,
+}
+
+/// The offer is identified by its currency, price and creator.
+pub type OfferId = (Option<ContractId>, Price, ActorId);
+
+/// The token attributes as `(trait, value)` pairs, e.g. `("background", "gold")`.
+pub type Attributes = Vec<(String, String)>;
+
+/// The query the marketplace sends to the NFT contract to check
+/// the attributes of the token for which the attribute-scoped collection offer is accepted.
+/// The NFT contract must reply with [`TokenAttributes`].
+///
+/// The index of the query is out of the range of `NFTAction`,
+/// so the NFT contracts that don't store the attributes fail to decode it
+/// instead of handling it as another action.
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum NFTAttributesAction {
+    #[codec(index = 255)]
+    TokenAttributes { token_id: TokenId },
+}
+
+/// The reply of the NFT contract to [`NFTAttributesAction::TokenAttributes`].
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub struct TokenAttributes {
+    pub token_id: TokenId,
+    pub attributes: Attributes,
+}
+
+/// Offers for any token of the NFT collection.
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone, Default)]
+pub struct CollectionOffers {
+    pub offers: BTreeMap<OfferId, Offer>,
+    /// pending transactions of offer creators
+    pub tx: BTreeMap<ActorId, (TransactionId, MarketTx)>,
 }
 
 #[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone, Default)]
@@ -83,17 +352,46 @@ pub struct Item {
     pub owner: ActorId,
     pub ft_contract_id: Option<ContractId>,
     pub price: Option<Price>,
+    pub expires_at: Option<u64>,
     pub auction: Option<Auction>,
-    pub offers: BTreeMap<(Option<ContractId>, Price), ActorId>,
+    pub dutch_auction: Option<DutchAuction>,
+    pub sealed_auction: Option<SealedAuction>,
+    pub offers: BTreeMap<OfferId, Offer>,
     pub tx: Option<(TransactionId, MarketTx)>,
 }
 
+/// The way the offers are handled when the item is removed by [`MarketAction::RemoveItem`].
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone, Copy)]
+pub enum OffersPolicy {
+    /// The offers are returned to their creators and the item is deleted.
+    Refund,
+    /// The item is kept off the sale while there are offers on it,
+    /// otherwise it is deleted.
+    Keep,
+}
+
+/// The listing data of [`MarketAction::BatchAddMarketData`].
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo, Clone)]
+pub struct MarketData {
+    /// the NFT contract address
+    pub nft_contract_id: ContractId,
+    /// the fungible token contract address (If it is `None` then the item is traded for the native value)
+    pub ft_contract_id: Option<ContractId>,
+    /// the NFT id
+    pub token_id: TokenId,
+    /// the NFT price (if it is `None` then the item is not on the sale)
+    pub price: Option<u128>,
+    /// the time after which the NFT can't be bought for the price
+    /// (if it is `None` then the listing doesn't expire)
+    pub expires_at: Option<u64>,
+}
+
 #[derive(Debug, Encode, Decode, TypeInfo)]
 pub enum MarketAction {
     /// Adds NFT contract addresses that can be listed on marketplace.
     ///
     /// # Requirements:
-    /// Only admin can add approved NFT accounts.
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::ContractCurator`] role.
     ///
     /// On success replies [`MarketEvent::NftContractAdded`].
     AddNftContract(
@@ -104,7 +402,7 @@ pub enum MarketAction {
     /// Adds the contract addresses of fungible tokens with which users can pay for NFTs.
     ///
     /// # Requirements:
-    /// Only admin can add approved fungible-token accounts.
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::ContractCurator`] role.
     ///
     /// On success replies [`MarketEvent::FtContractAdded`].
     AddFTContract(
@@ -112,16 +410,174 @@ pub enum MarketAction {
         ContractId,
     ),
 
+    /// Removes the NFT contract address from the approved ones.
+    ///
+    /// The NFTs of the contract can't be listed, sold, bid on or traded through offers anymore,
+    /// but the running auctions can be settled, the pending transactions can be rerun,
+    /// and the items, offers and bids can be removed or withdrawn.
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::ContractCurator`] role.
+    ///
+    /// On success replies [`MarketEvent::NftContractRemoved`].
+    RemoveNftContract(
+        /// the NFT contract address
+        ContractId,
+    ),
+
+    /// Removes the fungible-token contract address from the approved ones.
+    ///
+    /// The NFTs that are priced in the tokens of the contract can't be sold or bid on anymore
+    /// and the new offers in these tokens are rejected. As for the removed NFT contracts,
+    /// the running auctions can be settled and the escrowed tokens can be withdrawn.
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::ContractCurator`] role.
+    ///
+    /// On success replies [`MarketEvent::FtContractRemoved`].
+    RemoveFtContract(
+        /// the FT contract address
+        ContractId,
+    ),
+
+    /// Updates the treasury fee.
+    ///
+    /// The new fee takes effect after the `delay`, so listers can see the change coming.
+    /// The sales made before that are charged the current fee,
+    /// the pending transactions are rerun with the fee of their start.
+    /// The new update replaces the scheduled one.
+    ///
+    /// # Requirements:
+    /// * [`msg::source()`](gstd::msg::source) must have the [`Role::FeeManager`] role.
+    /// * `fee` must be in the same bounds as at the initialization.
+    ///
+    /// On success replies [`MarketEvent::TreasuryFeeUpdated`] if `delay` is zero,
+    /// otherwise [`MarketEvent::TreasuryFeeScheduled`].
+    UpdateTreasuryFee {
+        /// the new treasury fee in basis points
+        fee: u16,
+        /// the time in milliseconds before the new fee takes effect
+        delay: u64,
+    },
+
+    /// Changes the account that receives the treasury fee.
+    /// The pending transactions are rerun with the account of their start.
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::FeeManager`] role.
+    ///
+    /// On success replies [`MarketEvent::TreasuryUpdated`].
+    UpdateTreasury(
+        /// the new treasury account
+        ActorId,
+    ),
+
+    /// Sets the treasury fee charged for the sales of the NFT contract instead of the marketplace fee.
+    ///
+    /// Like [`MarketAction::UpdateTreasuryFee`], the new fee takes effect after the `delay`
+    /// and the new update replaces the scheduled one.
+    ///
+    /// # Requirements:
+    /// * [`msg::source()`](gstd::msg::source) must have the [`Role::FeeManager`] role.
+    /// * `nft_contract_id` must be in the list of `approved_nft_contracts`.
+    /// * `fee` must be in the same bounds as the marketplace fee.
+    ///
+    /// On success replies [`MarketEvent::CollectionFeeUpdated`] if `delay` is zero,
+    /// otherwise [`MarketEvent::CollectionFeeScheduled`].
+    SetCollectionFee {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the fee in basis points or `None` to charge the marketplace fee
+        fee: Option<u16>,
+        /// the time in milliseconds before the new fee takes effect
+        delay: u64,
+    },
+
+    /// Stops the trading on the marketplace or of the NFT contract.
+    ///
+    /// New listings, auctions, bids, offers and purchases are rejected with [`MarketErr::Paused`],
+    /// but the items, offers and bids can still be removed or withdrawn,
+    /// the auctions can be settled and the pending transactions can be rerun.
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::Pauser`] role.
+    ///
+    /// On success replies [`MarketEvent::Paused`].
+    Pause {
+        /// the NFT contract address or `None` to pause the whole marketplace
+        nft_contract_id: Option<ContractId>,
+    },
+
+    /// Resumes the trading stopped via [`MarketAction::Pause`].
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::Pauser`] role.
+    ///
+    /// On success replies [`MarketEvent::Unpaused`].
+    Unpause {
+        /// the NFT contract address or `None` to unpause the whole marketplace
+        nft_contract_id: Option<ContractId>,
+    },
+
+    /// Proposes the account as the new admin.
+    /// The admin is changed only after the account accepts the role via [`MarketAction::AcceptAdmin`].
+    ///
+    /// # Requirements:
+    /// Only admin can propose the new admin (the [`Role::Admin`] role isn't enough).
+    ///
+    /// On success replies [`MarketEvent::AdminProposed`].
+    ProposeAdmin(
+        /// the new admin address
+        ActorId,
+    ),
+
+    /// Accepts the admin role proposed via [`MarketAction::ProposeAdmin`].
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must be the proposed admin.
+    ///
+    /// On success replies [`MarketEvent::AdminChanged`].
+    AcceptAdmin,
+
+    /// Grants the role to the account.
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::Admin`] role.
+    ///
+    /// On success replies [`MarketEvent::RoleGranted`].
+    GrantRole {
+        /// the account that gets the role
+        account: ActorId,
+        /// the granted role
+        role: Role,
+    },
+
+    /// Revokes the role from the account.
+    ///
+    /// # Requirements:
+    /// [`msg::source()`](gstd::msg::source) must have the [`Role::Admin`] role.
+    ///
+    /// On success replies [`MarketEvent::RoleRevoked`].
+    RevokeRole {
+        /// the account that loses the role
+        account: ActorId,
+        /// the revoked role
+        role: Role,
+    },
+
     /// Adds data on market item.
     /// If the item of that NFT does not exist on the marketplace then it will be listed.
     /// If the item exists then that action is used to change the price or suspend the sale.
+    /// It also stops an ongoing Dutch auction on the item.
     ///
     /// # Requirements
     /// * [`msg::source()`](gstd::msg::source) must be the NFT owner
     /// * `nft_contract_id` must be in the list of `approved_nft_contracts`
     /// * if item already exists, then it cannot be changed if there is an active auction
+    /// * `expires_at` must be in the future
     ///
     /// On success replies [`MarketEvent::MarketDataAdded`].
+    /// If there is an active auction on the item replies [`MarketErr::ItemOnAuction`].
     AddMarketData {
         /// the NFT contract address
         nft_contract_id: ContractId,
@@ -131,15 +587,79 @@ pub enum MarketAction {
         token_id: TokenId,
         /// the NFT price (if it is `None` then the item is not on the sale)
         price: Option<u128>,
+        /// the time after which the NFT can't be bought for the price
+        /// (if it is `None` then the listing doesn't expire)
+        expires_at: Option<u64>,
+    },
+
+    /// Adds data on several market items like [`MarketAction::AddMarketData`].
+    ///
+    /// The NFT owners are requested from the NFT contracts at once,
+    /// and the items that don't meet the requirements are skipped.
+    ///
+    /// On success replies [`MarketEvent::BatchResult`] with the result for each item.
+    BatchAddMarketData(Vec<MarketData>),
+
+    /// Removes several items from the sale.
+    ///
+    /// # Requirements
+    /// * [`msg::source()`](gstd::msg::source) must be the owner of the items
+    /// * there must be no active auction on the items
+    ///
+    /// The items that don't meet the requirements are skipped.
+    ///
+    /// On success replies [`MarketEvent::BatchResult`] with the result for each item.
+    BatchRemoveListings(
+        /// the NFT contract addresses and ids
+        Vec<(ContractId, TokenId)>,
+    ),
+
+    /// Updates the owner of the item from the NFT contract.
+    /// If the NFT was transferred outside the marketplace,
+    /// the item is removed from the sale.
+    ///
+    /// # Requirements
+    /// * there must be no active auction and no pending transaction on the item
+    ///
+    /// Anyone can sync the item.
+    ///
+    /// On success replies [`MarketEvent::ItemSynced`].
+    SyncItem {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+    },
+
+    /// Removes the item from the marketplace.
+    ///
+    /// # Requirements
+    /// * [`msg::source()`](gstd::msg::source) must be the NFT owner
+    /// * there must be no active auction and no pending transaction on the item
+    ///
+    /// On success replies [`MarketEvent::ItemRemoved`].
+    RemoveItem {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// what to do with the offers on the item
+        offers_policy: OffersPolicy,
     },
 
     /// Sells the NFT.
     ///
+    /// If there is a Dutch auction on the item, the NFT is sold
+    /// at the auction price at the current block. The attached value above the price is returned,
+    /// unless it's too small to be sent (not greater than the `MINIMUM_VALUE`), then it's paid to the seller.
+    ///
     /// # Requirements:
     /// * The NFT item must exists and be on sale.
     /// * If the NFT is sold for a native Gear value, then a buyer must attach value equals to the price.
     /// * If the NFT is sold for fungible tokens then a buyer must have enough tokens in the fungible token contract.
     /// * There must be no an opened auction on the item.
+    /// * The seller must still own the NFT, otherwise the item is removed from the sale
+    /// and the attached value is returned.
     ///
     /// On success replies [`MarketEvent::ItemSold`].
     BuyItem {
@@ -149,6 +669,29 @@ pub enum MarketAction {
         token_id: TokenId,
     },
 
+    /// Buys several NFTs like [`MarketAction::BuyItem`] spending no more than `max_total_price`.
+    ///
+    /// The items that can't be bought (e.g. sold or removed from the sale)
+    /// or don't fit into the remaining amount are skipped.
+    ///
+    /// # Requirements:
+    /// * The items must be traded in the same currency.
+    /// * If the NFTs are sold for a native Gear value, then a buyer must attach value equals to `max_total_price`,
+    /// the value that isn't spent is returned. The remainder that is too small to be returned
+    /// is paid to the seller of the item that leaves it.
+    /// * If the NFTs are sold for fungible tokens, then a buyer must attach no value.
+    /// * The price of the purchase that replies [`MarketErr::RerunTransaction`] counts against `max_total_price`.
+    ///
+    /// On success replies [`MarketEvent::ItemsBought`] with the price paid for each item.
+    BuyItems {
+        /// the NFT contract addresses and ids
+        items: Vec<(ContractId, TokenId)>,
+        /// the fungible token contract address (If it is `None` then the items are bought for the native value)
+        ft_contract_id: Option<ContractId>,
+        /// the maximum amount spent for all items
+        max_total_price: u128,
+    },
+
     /// Creates an auction for selected item.
     /// If the NFT item doesn't exist on the marketplace then it will be listed
     ///
@@ -171,6 +714,142 @@ pub enum MarketAction {
         bid_period: u64,
         /// the auction duration
         duration: u64,
+        /// the maximum total time the auction can be extended by bids made near its end
+        max_extension: u64,
+        /// the commitment to the hidden minimum price for which the NFT can be sold,
+        /// it's `blake2_256` hash of the SCALE-encoded tuple `(reserve_price: u128, salt: [u8; 32])`.
+        /// The reserve price is revealed by [`MarketAction::RevealReservePrice`],
+        /// if it isn't revealed or reached, the auction is cancelled at the settlement.
+        reserve_price_commitment: Option<[u8; 32]>,
+        /// the price for which the NFT can be bought immediately (see [`MarketAction::BuyNow`])
+        buy_now_price: Option<u128>,
+        /// the minimum amount by which a new bid must exceed the current one
+        bid_increment: BidIncrement,
+        /// whether to settle the auction automatically at its end
+        /// (the gas for the settlement is reserved from the message,
+        /// if it can't be reserved the auction isn't created)
+        auto_settlement: bool,
+    },
+
+    /// Creates a Dutch auction for selected item.
+    /// The price decreases from `start_price` to `floor_price` during the auction
+    /// and the first buyer buys the NFT via [`MarketAction::BuyItem`].
+    /// The auction that ended without a sale is removed by the next listing or auction of the item.
+    ///
+    /// Requirements:
+    /// * Only the item owner can start the auction.
+    /// * `nft_contract_id` must be in the list of `approved_nft_contracts`
+    /// * There must be no active auction.
+    /// * The item must not be on the sale.
+    ///
+    /// On success replies [`MarketEvent::DutchAuctionCreated`].
+    CreateDutchAuction {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the fungible token contract address (If it is `None` then the item is traded for the native value)
+        ft_contract_id: Option<ContractId>,
+        /// the NFT id
+        token_id: TokenId,
+        /// the price at the auction start
+        start_price: u128,
+        /// the price at the auction end
+        floor_price: u128,
+        /// the way the price decreases
+        decay: PriceDecay,
+        /// the auction duration
+        duration: u64,
+    },
+
+    /// Creates a sealed-bid auction for selected item.
+    ///
+    /// During the commit phase bidders submit hash commitments of their bids
+    /// with deposits (see [`MarketAction::CommitBid`]), during the reveal phase
+    /// they reveal the bids (see [`MarketAction::RevealBid`]).
+    /// The highest revealed bid wins.
+    ///
+    /// Requirements:
+    /// * Only the item owner can start the auction.
+    /// * `nft_contract_id` must be in the list of `approved_nft_contracts`
+    /// * There must be no active auction.
+    /// * The item must not be on the sale.
+    ///
+    /// On success replies [`MarketEvent::SealedAuctionCreated`].
+    CreateSealedAuction {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the fungible token contract address (If it is `None` then the item is traded for the native value)
+        ft_contract_id: Option<ContractId>,
+        /// the NFT id
+        token_id: TokenId,
+        /// the minimum price
+        min_price: u128,
+        /// the price the winner pays
+        pricing: SealedBidPricing,
+        /// the commit phase duration
+        commit_duration: u64,
+        /// the reveal phase duration
+        reveal_duration: u64,
+    },
+
+    /// Commits a bid to an ongoing sealed-bid auction.
+    ///
+    /// `commitment` is `blake2_256` hash of the SCALE-encoded tuple
+    /// `(bidder: ActorId, price: u128, salt: [u8; 32])`.
+    /// `deposit` is locked on the marketplace until the auction is settled,
+    /// so it must not be less than the bid price.
+    ///
+    /// # Requirements:
+    /// * The sealed-bid auction must be in the commit phase.
+    /// * A bidder can commit only one bid.
+    /// * `deposit` must not be less than the auction minimum price.
+    /// * If the NFT is sold for a native Gear value, then a bidder must attach value equals to the deposit.
+    /// * If the NFT is sold for fungible tokens then a bidder must have enough tokens in the fungible token contract.
+    ///
+    /// On success replies [`MarketEvent::SealedBidCommitted`].
+    CommitBid {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// the bid commitment
+        commitment: [u8; 32],
+        /// the deposit
+        deposit: u128,
+    },
+
+    /// Reveals a committed bid.
+    ///
+    /// # Requirements:
+    /// * The sealed-bid auction must be in the reveal phase.
+    /// * `price` and `salt` must match the commitment.
+    /// * `price` must be in the range from the auction minimum price to the deposit.
+    ///
+    /// On success replies [`MarketEvent::SealedBidRevealed`].
+    RevealBid {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// the bid price
+        price: u128,
+        /// the salt used in the commitment
+        salt: [u8; 32],
+    },
+
+    /// Settles the sealed-bid auction.
+    /// Returns deposits (except the price paid by the winner) to all bidders.
+    /// Ties between the highest bids are broken in favor of the bidder with the lowest [`ActorId`].
+    ///
+    /// Requirements:
+    /// * The reveal phase must be over.
+    ///
+    /// On successful auction replies [`MarketEvent::AuctionSettled`].
+    /// If no bids were revealed replies [`MarketEvent::AuctionCancelled`].
+    SettleSealedAuction {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
     },
 
     /// Adds a bid to an ongoing auction.
@@ -182,7 +861,11 @@ pub enum MarketAction {
     /// * If the NFT is sold for fungible tokens then a buyer must have   enough tokens in the fungible token contract.
     /// * `price` must be greater then the current offered price for that item.
     ///
+    /// If the bid is made less than `bid_period` before the auction end,
+    /// the auction is extended (but not beyond its `max_extension`).
+    ///
     /// On success replies [`MarketEvent::BidAdded`].
+    /// If the auction was extended replies [`MarketEvent::AuctionExtended`].
     AddBid {
         /// the NFT contract address.
         nft_contract_id: ContractId,
@@ -192,13 +875,67 @@ pub enum MarketAction {
         price: u128,
     },
 
+    /// Buys the NFT on an ongoing auction for the buy-now price.
+    /// The auction ends immediately and is settled to the caller.
+    ///
+    /// # Requirements:
+    /// * The auction must exists and have the buy-now price.
+    /// * The buy-now price must be greater then the current offered price for that item.
+    /// * If the NFT is sold for a native Gear value, then a buyer must attach value equals to the buy-now price.
+    /// * If the NFT is sold for fungible tokens then a buyer must have enough tokens in the fungible token contract.
+    ///
+    /// On success replies [`MarketEvent::AuctionSettled`].
+    BuyNow {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+    },
+
+    /// Reveals the hidden reserve price of the auction.
+    ///
+    /// # Requirements:
+    /// * Only the NFT owner can reveal the reserve price.
+    /// * The auction must not be over and have the reserve price commitment.
+    /// * `reserve_price` and `salt` must match the commitment.
+    ///
+    /// On success replies [`MarketEvent::ReservePriceRevealed`].
+    RevealReservePrice {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// the reserve price
+        reserve_price: u128,
+        /// the salt used in the commitment
+        salt: [u8; 32],
+    },
+
+    /// Cancels the auction and returns the NFT to the owner.
+    ///
+    /// # Requirements:
+    /// * The auction must exists.
+    /// * [`msg::source()`](gstd::msg::source) must be the NFT owner if no bids were made
+    /// or have the [`Role::Moderator`] role. In the latter case the leading bid is returned to the bidder.
+    ///
+    /// On success replies [`MarketEvent::AuctionCancelled`].
+    CancelAuction {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+    },
+
     /// Settles the auction.
     ///
     /// Requirements:
     /// * The auction must be over.
     ///
     /// On successful auction replies [`MarketEvent::AuctionSettled`].
-    /// If no bids were made replies [`MarketEvent::AuctionCancelled`].
+    /// If no bids were made or the reserve price wasn't revealed or reached replies [`MarketEvent::AuctionCancelled`].
+    /// The bid for the buy-now price doesn't depend on the reserve price.
+    /// If the automatic settlement arrives before the end of the extended auction,
+    /// it's rescheduled and replies [`MarketEvent::AutoSettlementRescheduled`].
     SettleAuction {
         /// the NFT contract address
         nft_contract_id: ContractId,
@@ -214,7 +951,8 @@ pub enum MarketAction {
     /// * If a user makes an offer in native Gear value, then he must attach value equals to the price indicated in the arguments.
     /// * If a user makes an offer in fungible tokens then he must have  enough tokens in the fungible token contract.
     /// * The price can not be equal to 0.
-    /// * There must be no identical offers on the item.
+    /// * There must be no identical offers from the user on the item.
+    /// * `expires_at` must be in the future.
     ///
     /// On success replies [`MarketEvent::OfferAdded`].
     AddOffer {
@@ -226,14 +964,180 @@ pub enum MarketAction {
         token_id: TokenId,
         /// the offer price
         price: u128,
+        /// the time after which the offer can't be accepted
+        /// and can be refunded via [`MarketAction::CleanupExpiredOffers`]
+        /// (if it is `None` then the offer doesn't expire)
+        expires_at: Option<u64>,
+    },
+
+    /// Adds a price offer for any token of the NFT collection.
+    ///
+    /// Requirements:
+    /// * `nft_contract_id` must be in the list of `approved_nft_contracts`.
+    /// * If a user makes an offer in native Gear value, then he must attach value equals to the price indicated in the arguments.
+    /// * If a user makes an offer in fungible tokens then he must have  enough tokens in the fungible token contract.
+    /// * The price can not be equal to 0.
+    /// * There must be no identical offers from the user on the collection.
+    /// * `expires_at` must be in the future.
+    ///
+    /// If `attributes` are set, the offer can be accepted only for the token that has all of them.
+    /// The NFT contract must answer [`NFTAttributesAction`] to check the attributes.
+    ///
+    /// On success replies [`MarketEvent::CollectionOfferAdded`].
+    AddCollectionOffer {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the FT contract address (if it is `None, the offer is made for the native value)
+        ft_contract_id: Option<ContractId>,
+        /// the offer price
+        price: Price,
+        /// the time after which the offer can't be accepted
+        /// and can be refunded via [`MarketAction::CleanupExpiredCollectionOffers`]
+        /// (if it is `None` then the offer doesn't expire)
+        expires_at: Option<u64>,
+        /// the attributes the token must have (if they are empty, the offer is made for any token)
+        attributes: Attributes,
+    },
+
+    /// Accepts a collection offer for the token of the caller.
+    ///
+    /// Requirements:
+    /// * [`msg::source()`](gstd::msg::source) must be the NFT owner.
+    /// * There must be no ongoing auction on the item.
+    /// * The offer with indicated params must exist and must not be expired.
+    /// * The token must have the attributes of the offer, otherwise replies [`MarketErr::AttributesNotMatched`].
+    ///
+    /// The fixed-price listing of the NFT is cancelled.
+    ///
+    /// On success replies [`MarketEvent::OfferAccepted`].
+    AcceptCollectionOffer {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// the fungible token contract address
+        ft_contract_id: Option<ContractId>,
+        /// the offer price
+        price: Price,
+        /// the offer creator
+        buyer: ActorId,
+    },
+
+    /// Withdraws tokens of the collection offer.
+    ///
+    /// Requirements:
+    /// * The offer with indicated params must be made by [`msg::source()`](gstd::msg::source).
+    ///
+    /// On success replies [`MarketEvent::CollectionOfferWithdrawn`].
+    WithdrawCollectionOffer {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the FT contract address (if it is `None, the offer is made for the native value)
+        ft_contract_id: Option<ContractId>,
+        /// the offer price
+        price: Price,
+    },
+
+    /// Proposes a different price to the offer creator.
+    ///
+    /// Requirements:
+    /// * Only owner can make a counter-offer.
+    /// * There must be no ongoing auction.
+    /// * The offer with indicated params must exist and must not be expired.
+    /// * The counter price must differ from the offered one and can not be equal to 0.
+    /// * If the offer is made for the native value, the counter price can't be lower than the offered one
+    /// by `MINIMUM_VALUE` or less, since such excess can't be returned to the offer creator.
+    ///
+    /// On success replies [`MarketEvent::CounterOfferMade`].
+    CounterOffer {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// the fungible token contract address
+        ft_contract_id: Option<ContractId>,
+        /// the offer price
+        price: Price,
+        /// the offer creator
+        buyer: ActorId,
+        /// the price proposed by the owner
+        counter_price: Price,
+    },
+
+    /// Accepts the counter-offer and buys the NFT for the counter price.
+    /// The difference between the counter and the offered price is added to the escrow
+    /// or returned to the buyer.
+    ///
+    /// Requirements:
+    /// * The offer with indicated params must be made by [`msg::source()`](gstd::msg::source)
+    /// and have the counter-offer from the current NFT owner.
+    /// * If the counter price is greater than the offered one, the buyer must attach the missing native value
+    /// or have enough tokens in the fungible token contract.
+    /// * The NFT owner must still own the NFT, otherwise the item is removed from the sale
+    /// and the attached value is returned.
+    ///
+    /// On success replies [`MarketEvent::OfferAccepted`].
+    AcceptCounterOffer {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// the fungible token contract address
+        ft_contract_id: Option<ContractId>,
+        /// the offer price
+        price: Price,
+    },
+
+    /// Declines the counter-offer, the offer stays unchanged.
+    ///
+    /// Requirements:
+    /// * The offer with indicated params must be made by [`msg::source()`](gstd::msg::source)
+    /// and have the counter-offer.
+    ///
+    /// On success replies [`MarketEvent::CounterOfferDeclined`].
+    DeclineCounterOffer {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+        /// the fungible token contract address
+        ft_contract_id: Option<ContractId>,
+        /// the offer price
+        price: Price,
+    },
+
+    /// Returns tokens of all expired offers on the item to their creators.
+    ///
+    /// Requirements:
+    /// * NFT item must exists and be listed on the marketplace.
+    ///
+    /// Anyone can clean up expired offers.
+    ///
+    /// On success replies [`MarketEvent::ExpiredOffersCleaned`].
+    CleanupExpiredOffers {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
+        /// the NFT id
+        token_id: TokenId,
+    },
+
+    /// Returns tokens of all expired collection offers on the NFT contract to their creators.
+    ///
+    /// The offers of the creators with the pending transactions on the collection are skipped.
+    ///
+    /// Anyone can clean up expired offers.
+    ///
+    /// On success replies [`MarketEvent::ExpiredCollectionOffersCleaned`].
+    CleanupExpiredCollectionOffers {
+        /// the NFT contract address
+        nft_contract_id: ContractId,
     },
 
     /// Withdraws tokens.
     ///
     /// Requirements:
     /// * NFT item must exists and be listed on the marketplace.
-    /// * Only the offer creator can withdraw his tokens.
-    /// * The offer with indicated hash must exist.
+    /// * The offer with indicated params must be made by [`msg::source()`](gstd::msg::source).
     ///
     /// On success replies [`MarketEvent::Withdrawn`].
     Withdraw {
@@ -253,7 +1157,8 @@ pub enum MarketAction {
     /// * NFT item must exists and be listed on the marketplace.
     /// * Only owner can accept offer.
     /// * There must be no ongoing auction.
-    /// * The offer with indicated hash must exist.
+    /// * The offer with indicated params must exist and must not be expired.
+    /// * The owner must still own the NFT, otherwise the item is removed from the sale.
     ///
     /// On success replies [`MarketEvent::ItemSold`].
     AcceptOffer {
@@ -265,6 +1170,107 @@ pub enum MarketAction {
         ft_contract_id: Option<ContractId>,
         /// the offer price
         price: Price,
+        /// the offer creator
+        buyer: ActorId,
+    },
+
+    /// Lists several NFTs as one bundle sold for a single price.
+    ///
+    /// Requirements:
+    /// * [`msg::source()`](gstd::msg::source) must be the owner of all NFTs.
+    /// * The NFT contracts must be in the list of `approved_nft_contracts`.
+    /// * The bundle must not be empty or contain the same NFT twice.
+    /// * There must be no ongoing auctions on the NFTs.
+    ///
+    /// On success replies [`MarketEvent::BundleAdded`].
+    AddBundle {
+        /// the NFT contract addresses and ids
+        tokens: Vec<(ContractId, TokenId)>,
+        /// the fungible token contract address (If it is `None` then the bundle is traded for the native value)
+        ft_contract_id: Option<ContractId>,
+        /// the bundle price
+        price: Price,
+    },
+
+    /// Removes the bundle from the sale.
+    ///
+    /// Requirements:
+    /// * Only the bundle owner can remove it.
+    /// * There must be no pending sale of the bundle.
+    ///
+    /// On success replies [`MarketEvent::BundleRemoved`].
+    RemoveBundle {
+        /// the bundle id
+        bundle_id: BundleId,
+    },
+
+    /// Sells all NFTs of the bundle.
+    ///
+    /// If one of the NFTs can't be transferred or the buyer can't pay,
+    /// the NFTs are returned to the seller and the sale fails.
+    ///
+    /// Requirements:
+    /// * The bundle must exist.
+    /// * If the bundle is sold for a native Gear value, then a buyer must attach value equals to the price.
+    /// * If the bundle is sold for fungible tokens then a buyer must have enough tokens in the fungible token contract.
+    ///
+    /// On success replies [`MarketEvent::BundleSold`].
+    BuyBundle {
+        /// the bundle id
+        bundle_id: BundleId,
+    },
+
+    /// Proposes to exchange NFTs of [`msg::source()`](gstd::msg::source)
+    /// for the NFTs of the counterparty.
+    /// The proposer can add fungible tokens or the native value to the NFTs.
+    ///
+    /// Requirements:
+    /// * [`msg::source()`](gstd::msg::source) must be the owner of the offered NFTs
+    /// and the counterparty must be the owner of the requested NFTs.
+    /// * The NFT contracts must be in the list of `approved_nft_contracts`.
+    /// * Both sides of the swap must not be empty and the NFTs must not repeat.
+    /// * If the payment is made in the native value, then it must be attached to the message.
+    ///
+    /// On success replies [`MarketEvent::SwapProposed`].
+    ProposeSwap {
+        /// the counterparty
+        counterparty: ActorId,
+        /// the NFT contract addresses and ids of the proposer
+        offered: Vec<(ContractId, TokenId)>,
+        /// the NFT contract addresses and ids of the counterparty
+        requested: Vec<(ContractId, TokenId)>,
+        /// the fungible token contract address (If it is `None` then the payment is made in the native value)
+        ft_contract_id: Option<ContractId>,
+        /// the additional payment to the counterparty
+        payment: Price,
+    },
+
+    /// Accepts the swap and exchanges the NFTs.
+    ///
+    /// If one of the NFTs can't be transferred or the proposer can't pay,
+    /// the NFTs are returned to their owners and the swap stays proposed.
+    ///
+    /// Requirements:
+    /// * Only the counterparty can accept the swap.
+    /// * The marketplace must be approved to transfer the NFTs of both sides
+    /// and the fungible tokens of the proposer.
+    ///
+    /// On success replies [`MarketEvent::SwapCompleted`].
+    AcceptSwap {
+        /// the swap id
+        swap_id: SwapId,
+    },
+
+    /// Cancels the swap and returns the native value to the proposer.
+    ///
+    /// Requirements:
+    /// * Only the proposer or the counterparty can cancel the swap.
+    /// * There must be no pending transaction of the swap.
+    ///
+    /// On success replies [`MarketEvent::SwapCancelled`].
+    CancelSwap {
+        /// the swap id
+        swap_id: SwapId,
     },
 }
 
@@ -272,6 +1278,39 @@ pub enum MarketAction {
 pub enum MarketEvent {
     NftContractAdded(ContractId),
     FtContractAdded(ContractId),
+    NftContractRemoved(ContractId),
+    FtContractRemoved(ContractId),
+    TreasuryFeeScheduled {
+        fee: u16,
+        effective_at: u64,
+    },
+    TreasuryFeeUpdated(u16),
+    TreasuryUpdated(ActorId),
+    Paused {
+        nft_contract_id: Option<ContractId>,
+    },
+    Unpaused {
+        nft_contract_id: Option<ContractId>,
+    },
+    CollectionFeeScheduled {
+        nft_contract_id: ContractId,
+        fee: Option<u16>,
+        effective_at: u64,
+    },
+    CollectionFeeUpdated {
+        nft_contract_id: ContractId,
+        fee: Option<u16>,
+    },
+    AdminProposed(ActorId),
+    AdminChanged(ActorId),
+    RoleGranted {
+        account: ActorId,
+        role: Role,
+    },
+    RoleRevoked {
+        account: ActorId,
+        role: Role,
+    },
     MarketDataAdded {
         nft_contract_id: ContractId,
         token_id: TokenId,
@@ -292,6 +1331,44 @@ pub enum MarketEvent {
         token_id: TokenId,
         price: u128,
     },
+    AuctionExtended {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        price: u128,
+        new_end: u64,
+    },
+    AutoSettlementRescheduled {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        block: u32,
+    },
+    ReservePriceRevealed {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        reserve_price: u128,
+    },
+    DutchAuctionCreated {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        start_price: u128,
+        floor_price: u128,
+    },
+    SealedAuctionCreated {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        min_price: u128,
+    },
+    SealedBidCommitted {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        account: ActorId,
+    },
+    SealedBidRevealed {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        account: ActorId,
+        price: u128,
+    },
     AuctionSettled {
         nft_contract_id: ContractId,
         token_id: TokenId,
@@ -324,6 +1401,74 @@ pub enum MarketEvent {
         token_id: TokenId,
         price: u128,
     },
+    CounterOfferMade {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        buyer: ActorId,
+        price: u128,
+        counter_price: u128,
+    },
+    CounterOfferDeclined {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        buyer: ActorId,
+        price: u128,
+    },
+    CollectionOfferAdded {
+        nft_contract_id: ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: u128,
+    },
+    CollectionOfferWithdrawn {
+        nft_contract_id: ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: u128,
+    },
+    ExpiredOffersCleaned {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        count: u32,
+    },
+    ExpiredCollectionOffersCleaned {
+        nft_contract_id: ContractId,
+        count: u32,
+    },
+    BundleAdded {
+        bundle_id: BundleId,
+        owner: ActorId,
+        price: u128,
+    },
+    BundleRemoved {
+        bundle_id: BundleId,
+    },
+    BundleSold {
+        bundle_id: BundleId,
+        owner: ActorId,
+        price: u128,
+    },
+    SwapProposed {
+        swap_id: SwapId,
+        proposer: ActorId,
+        counterparty: ActorId,
+    },
+    SwapCompleted {
+        swap_id: SwapId,
+    },
+    SwapCancelled {
+        swap_id: SwapId,
+    },
+    BatchResult(Vec<(ContractId, TokenId, Result<(), MarketErr>)>),
+    ItemRemoved {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+    },
+    ItemSynced {
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        owner: ActorId,
+        is_changed: bool,
+    },
+    ItemsBought(Vec<(ContractId, TokenId, Result<u128, MarketErr>)>),
     TransactionFailed,
     RerunTransaction,
     TransferValue,
@@ -351,6 +1496,29 @@ pub enum MarketErr {
     OfferAlreadyExists,
     OfferShouldAcceptedByOwner,
     OfferIsNotExists,
+    BidAlreadyCommitted,
+    BidIsNotCommitted,
+    RevealPhaseIsNotActive,
+    WrongCommitment,
+    BuyNowPriceIsNotSet,
+    BidIncrementTooLow,
+    AutoSettlementNotScheduled,
+    AuctionHasBids,
+    ListingExpired,
+    OfferExpired,
+    CounterOfferIsNotExists,
+    BundleDoesNotExists,
+    InvalidBundle,
+    SwapDoesNotExists,
+    InvalidSwap,
+    WrongCurrency,
+    SpendLimitExceeded,
+    ItemOwnerChanged,
+    MissingRole,
+    WrongTreasuryFee,
+    Paused,
+    ReservePriceIsNotSet,
+    AttributesNotMatched,
 }
 
 pub fn all_items(state: <MarketMetadata as Metadata>::State) -> Vec<Item> {
@@ -363,3 +1531,23 @@ pub fn item_info(state: <MarketMetadata as Metadata>::State, args: &ItemInfoArgs
         .get(&(args.nft_contract_id, args.token_id))
         .cloned()
 }
+
+/// Returns all items with the listings that expired before the `timestamp` cleared,
+/// i.e. such items keep their auctions and offers but have no price and expiry.
+///
+/// The state functions don't have access to the current block, so the caller provides it.
+pub fn active_items(state: <MarketMetadata as Metadata>::State, timestamp: u64) -> Vec<Item> {
+    all_items(state)
+        .into_iter()
+        .map(|mut item| {
+            if item
+                .expires_at
+                .map_or(false, |expires_at| expires_at < timestamp)
+            {
+                item.price = None;
+                item.expires_at = None;
+            }
+            item
+        })
+        .collect()
+}
@@ -1,15 +1,25 @@
 use crate::{
-    contract::{MarketHandler, BASE_PERCENT, MINIMUM_VALUE},
-    nft_messages::{nft_transfer, payouts, Payout},
+    contract::{refund_value, MarketHandler, MINIMUM_VALUE},
+    dutch_auction::clear_ended_dutch_auction,
+    fees::{basis_points_share, sale_payouts, FeeHandler},
+    nft_messages::{nft_transfer, Payout},
     payment::transfer_tokens,
 };
-use gstd::{exec, msg, prelude::*, ActorId};
+use gstd::{exec, msg, prelude::*, ActorId, ReservationId};
 use market_io::{Market, MarketEvent, *};
+use sp_core_hashing::blake2_256;
 
 const MIN_BID_PERIOD: u64 = 60_000;
+/// The gas reserved for the automatic settlement of the auction.
+const AUTO_SETTLEMENT_GAS: u64 = 50_000_000_000;
+/// The gas kept by the automatic settlement message to reschedule itself.
+const RESCHEDULING_GAS: u64 = 5_000_000_000;
+/// The block duration assumed until the marketplace has seen any blocks.
+const MIN_BLOCK_DURATION: u64 = 1_000;
 
 #[async_trait::async_trait]
 pub trait AuctionHandler {
+    #[allow(clippy::too_many_arguments)]
     async fn create_auction(
         &mut self,
         nft_contract_id: &ContractId,
@@ -18,6 +28,11 @@ pub trait AuctionHandler {
         min_price: Price,
         bid_period: u64,
         duration: u64,
+        max_extension: u64,
+        reserve_price_commitment: Option<[u8; 32]>,
+        buy_now_price: Option<Price>,
+        bid_increment: BidIncrement,
+        auto_settlement: bool,
     ) -> Result<MarketEvent, MarketErr>;
 
     /// Settles the auction.
@@ -30,7 +45,9 @@ pub trait AuctionHandler {
     /// * `token_id`: the NFT id
     ///
     /// On success auction replies [`MarketEvent::AuctionSettled`].
-    /// If no bids were made replies [`MarketEvent::AuctionCancelled`].
+    /// If no bids were made or the reserve price wasn't reached replies [`MarketEvent::AuctionCancelled`].
+    /// If the automatic settlement arrives before the end of the auction,
+    /// it's rescheduled and replies [`MarketEvent::AutoSettlementRescheduled`].
     #[allow(unused_must_use)]
     async fn settle_auction(
         &mut self,
@@ -44,10 +61,56 @@ pub trait AuctionHandler {
         token_id: TokenId,
         price: Price,
     ) -> Result<MarketEvent, MarketErr>;
+
+    async fn buy_now(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    /// Reveals the reserve price committed at the creation of the auction.
+    ///
+    /// Requirements:
+    /// * Only the NFT owner can reveal the reserve price.
+    /// * The auction must not be over.
+    /// * `reserve_price` and `salt` must match the commitment.
+    ///
+    /// Arguments:
+    /// * `nft_contract_id`: the NFT contract address
+    /// * `token_id`: the NFT id
+    /// * `reserve_price`: the reserve price
+    /// * `salt`: the salt used in the commitment
+    ///
+    /// On success replies [`MarketEvent::ReservePriceRevealed`].
+    fn reveal_reserve_price(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        reserve_price: Price,
+        salt: [u8; 32],
+    ) -> Result<MarketEvent, MarketErr>;
+
+    /// Cancels the auction.
+    ///
+    /// Requirements:
+    /// * Only the NFT owner can cancel the auction if no bids were made.
+    /// * The account with the [`Role::Moderator`] role can cancel the auction at any time, the leading bid is returned to the bidder.
+    ///
+    /// Arguments:
+    /// * `nft_contract_id`: the NFT contract address
+    /// * `token_id`: the NFT id
+    ///
+    /// On success replies [`MarketEvent::AuctionCancelled`].
+    async fn cancel_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr>;
 }
 
 #[async_trait::async_trait]
 impl AuctionHandler for Market {
+    #[allow(clippy::too_many_arguments)]
     async fn create_auction(
         &mut self,
         nft_contract_id: &ContractId,
@@ -56,10 +119,16 @@ impl AuctionHandler for Market {
         min_price: Price,
         bid_period: u64,
         duration: u64,
+        max_extension: u64,
+        reserve_price_commitment: Option<[u8; 32]>,
+        buy_now_price: Option<Price>,
+        bid_increment: BidIncrement,
+        auto_settlement: bool,
     ) -> Result<MarketEvent, MarketErr> {
         self.check_approved_nft_contract(nft_contract_id);
         self.check_approved_ft_contract(ft_contract_id);
         let contract_and_token_id = (*nft_contract_id, token_id);
+        let block_duration = block_duration(self);
 
         if let Some(item) = self.items.get_mut(&contract_and_token_id) {
             assert_eq!(
@@ -68,7 +137,11 @@ impl AuctionHandler for Market {
                 "Only owner has a right to add NFT to the marketplace and start the auction"
             );
 
-            if item.auction.is_some() {
+            clear_ended_dutch_auction(item);
+            if item.auction.is_some()
+                || item.dutch_auction.is_some()
+                || item.sealed_auction.is_some()
+            {
                 return Err(MarketErr::AuctionIsAlreadyExists);
             }
 
@@ -86,6 +159,12 @@ impl AuctionHandler for Market {
                 return Err(MarketErr::AuctionMinPriceIsZero);
             }
 
+            if let Some(buy_now_price) = buy_now_price {
+                if buy_now_price <= min_price {
+                    return Err(MarketErr::WrongPrice);
+                }
+            }
+
             if let Some((tx_id, tx)) = item.tx.clone() {
                 if tx == MarketTx::CreateAuction {
                     return create_auction_tx(
@@ -97,6 +176,11 @@ impl AuctionHandler for Market {
                         min_price,
                         bid_period,
                         duration,
+                        max_extension,
+                        reserve_price_commitment,
+                        buy_now_price,
+                        bid_increment,
+                        auto_settlement.then_some(block_duration),
                     )
                     .await;
                 } else {
@@ -117,6 +201,11 @@ impl AuctionHandler for Market {
                 min_price,
                 bid_period,
                 duration,
+                max_extension,
+                reserve_price_commitment,
+                buy_now_price,
+                bid_increment,
+                auto_settlement.then_some(block_duration),
             )
             .await
         } else {
@@ -134,7 +223,9 @@ impl AuctionHandler for Market {
     /// * `token_id`: the NFT id
     ///
     /// On success auction replies [`MarketEvent::AuctionSettled`].
-    /// If no bids were made replies [`MarketEvent::AuctionCancelled`].
+    /// If no bids were made or the reserve price wasn't reached replies [`MarketEvent::AuctionCancelled`].
+    /// If the automatic settlement arrives before the end of the auction,
+    /// it's rescheduled and replies [`MarketEvent::AutoSettlementRescheduled`].
     #[allow(unused_must_use)]
     async fn settle_auction(
         &mut self,
@@ -142,15 +233,42 @@ impl AuctionHandler for Market {
         token_id: TokenId,
     ) -> Result<MarketEvent, MarketErr> {
         let contract_and_token_id = (*nft_contract_id, token_id);
+        let treasury = self.treasury(nft_contract_id);
+        let block_duration = block_duration(self);
 
         if let Some(item) = self.items.get_mut(&contract_and_token_id) {
-            let Some(auction) = item.auction.clone() else {
+            let Some(auction) = item.auction.as_mut() else {
                 return Err(MarketErr::AuctionDoesNotExists);
             };
 
             if auction.ended_at > exec::block_timestamp() {
-                return Err(MarketErr::AuctionIsNotOver);
+                let is_auto_settlement = msg::source() == exec::program_id()
+                    && auction
+                        .auto_settlement
+                        .as_ref()
+                        .map_or(false, |settlement| settlement.message_id == msg::id());
+                if !is_auto_settlement {
+                    return Err(MarketErr::AuctionIsNotOver);
+                }
+
+                // the auction was extended after the settlement had been scheduled
+                // or the blocks were faster than estimated
+                let gas = exec::gas_available().saturating_sub(RESCHEDULING_GAS);
+                let auto_settlement = schedule_settlement(
+                    nft_contract_id,
+                    token_id,
+                    auction.ended_at,
+                    gas,
+                    block_duration,
+                );
+                auction.auto_settlement = auto_settlement.as_ref().ok().cloned();
+                return auto_settlement.map(|settlement| MarketEvent::AutoSettlementRescheduled {
+                    nft_contract_id: *nft_contract_id,
+                    token_id,
+                    block: settlement.block,
+                });
             }
+            let auction = auction.clone();
 
             if let Some((tx_id, tx)) = item.tx.clone() {
                 match tx {
@@ -167,16 +285,11 @@ impl AuctionHandler for Market {
                         )
                         .await;
                     }
-                    MarketTx::SettleAuction => {
+                    MarketTx::SettleAuction { treasury } => {
                         let price = auction.current_price;
-                        // calculate fee for treasury
-                        let treasury_fee =
-                            price * (self.treasury_fee * BASE_PERCENT) as u128 / 10_000u128;
-
-                        // payouts for NFT sale (includes royalty accounts and seller)
-                        let mut payouts =
-                            payouts(nft_contract_id, &item.owner, price - treasury_fee).await;
-                        payouts.insert(self.treasury_id, treasury_fee);
+                        // payouts for NFT sale (includes treasury, royalty accounts and seller)
+                        let payouts =
+                            sale_payouts(nft_contract_id, &item.owner, price, treasury).await;
                         return settle_auction_tx(
                             tx_id,
                             item,
@@ -194,16 +307,12 @@ impl AuctionHandler for Market {
             }
 
             let price = auction.current_price;
-            // calculate fee for treasury
-            let treasury_fee = price * (self.treasury_fee * BASE_PERCENT) as u128 / 10_000u128;
-
-            // payouts for NFT sale (includes royalty accounts and seller)
-            let mut payouts = payouts(nft_contract_id, &item.owner, price - treasury_fee).await;
-            payouts.insert(self.treasury_id, treasury_fee);
+            // payouts for NFT sale (includes treasury, royalty accounts and seller)
+            let payouts = sale_payouts(nft_contract_id, &item.owner, price, treasury).await;
 
             let tx_id = self.tx_id;
             self.tx_id = self.tx_id.wrapping_add(payouts.len() as u64);
-            item.tx = Some((tx_id, MarketTx::SettleAuction));
+            item.tx = Some((tx_id, MarketTx::SettleAuction { treasury }));
             settle_auction_tx(tx_id, item, &payouts, nft_contract_id, token_id, price).await
         } else {
             Err(MarketErr::ItemDoesNotExists)
@@ -217,6 +326,10 @@ impl AuctionHandler for Market {
         price: Price,
     ) -> Result<MarketEvent, MarketErr> {
         let contract_and_token_id = (*nft_contract_id, token_id);
+        if let Err(error) = self.check_item_trading_allowed(&contract_and_token_id) {
+            refund_value();
+            return Err(error);
+        }
 
         if let Some(item) = self.items.get_mut(&contract_and_token_id) {
             if let Some(auction) = item.auction.as_mut() {
@@ -227,27 +340,28 @@ impl AuctionHandler for Market {
                 let ft_id = match item.ft_contract_id {
                     Some(ft_id) => ft_id,
                     None => {
-                        if price <= auction.current_price {
-                            return Err(MarketErr::WrongPrice);
-                        }
+                        check_bid_price(auction, price)?;
 
                         assert!(msg::value() == price, "Not enough attached value");
 
-                        msg::send(
-                            auction.current_winner,
-                            MarketEvent::TransferValue,
-                            auction.current_price,
-                        )
-                        .expect("Error in sending value");
+                        if !auction.current_winner.is_zero() {
+                            msg::send(
+                                auction.current_winner,
+                                MarketEvent::TransferValue,
+                                auction.current_price,
+                            )
+                            .expect("Error in sending value");
+                        }
 
                         auction.current_price = price;
                         auction.current_winner = msg::source();
 
-                        return Ok(MarketEvent::BidAdded {
-                            nft_contract_id: *nft_contract_id,
+                        return Ok(bid_event(
+                            nft_contract_id,
                             token_id,
                             price,
-                        });
+                            extend_auction(auction),
+                        ));
                     }
                 };
 
@@ -302,8 +416,126 @@ impl AuctionHandler for Market {
             Err(MarketErr::ItemDoesNotExists)
         }
     }
+
+    async fn buy_now(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+        let auction = item
+            .auction
+            .as_ref()
+            .ok_or(MarketErr::AuctionDoesNotExists)?;
+        let price = auction
+            .buy_now_price
+            .ok_or(MarketErr::BuyNowPriceIsNotSet)?;
+
+        // the bid for the buy-now price ends the auction
+        self.add_bid(nft_contract_id, token_id, price).await?;
+
+        if let Some(auction) = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .and_then(|item| item.auction.as_mut())
+        {
+            auction.ended_at = exec::block_timestamp();
+        }
+
+        self.settle_auction(nft_contract_id, token_id).await
+    }
+
+    fn reveal_reserve_price(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        reserve_price: Price,
+        salt: [u8; 32],
+    ) -> Result<MarketEvent, MarketErr> {
+        let item = self
+            .items
+            .get_mut(&(*nft_contract_id, token_id))
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        if item.owner != msg::source() {
+            return Err(MarketErr::InvalidCaller);
+        }
+
+        let auction = item
+            .auction
+            .as_mut()
+            .ok_or(MarketErr::AuctionDoesNotExists)?;
+        if auction.ended_at < exec::block_timestamp() {
+            return Err(MarketErr::AuctionIsAlreadyEnded);
+        }
+
+        let commitment = auction
+            .reserve_price_commitment
+            .ok_or(MarketErr::ReservePriceIsNotSet)?;
+        if blake2_256(&(reserve_price, salt).encode()) != commitment {
+            return Err(MarketErr::WrongCommitment);
+        }
+
+        auction.reserve_price = Some(reserve_price);
+
+        Ok(MarketEvent::ReservePriceRevealed {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            reserve_price,
+        })
+    }
+
+    async fn cancel_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+        let is_moderator = self.has_role(&msg::source(), Role::Moderator);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+        let auction = item
+            .auction
+            .as_ref()
+            .ok_or(MarketErr::AuctionDoesNotExists)?;
+
+        if !is_moderator {
+            if msg::source() != item.owner {
+                return Err(MarketErr::InvalidCaller);
+            }
+            if !auction.current_winner.is_zero() {
+                return Err(MarketErr::AuctionHasBids);
+            }
+        }
+
+        if let Some((tx_id, tx)) = item.tx.clone() {
+            match tx {
+                MarketTx::CancelAuction => {
+                    return cancel_auction_tx(tx_id, item, nft_contract_id, token_id).await;
+                }
+                _ => {
+                    return Err(MarketErr::WrongTransaction);
+                }
+            }
+        }
+
+        let tx_id = self.tx_id;
+        self.tx_id = self.tx_id.wrapping_add(1);
+        item.tx = Some((tx_id, MarketTx::CancelAuction));
+        cancel_auction_tx(tx_id, item, nft_contract_id, token_id).await
+    }
 }
 
+/// Transfers the NFT to the marketplace and creates the auction.
+/// `block_duration` is only set if the auction is settled automatically.
 #[allow(clippy::too_many_arguments)]
 async fn create_auction_tx(
     tx_id: TransactionId,
@@ -314,7 +546,34 @@ async fn create_auction_tx(
     price: Price,
     bid_period: u64,
     duration: u64,
+    max_extension: u64,
+    reserve_price_commitment: Option<[u8; 32]>,
+    buy_now_price: Option<Price>,
+    bid_increment: BidIncrement,
+    block_duration: Option<u64>,
 ) -> Result<MarketEvent, MarketErr> {
+    let started_at = exec::block_timestamp();
+    let ended_at = started_at + duration;
+
+    // the settlement is scheduled before the NFT transfer, so the auction isn't created without it.
+    // If the transfer fails, the settlement message just fails as there is no auction.
+    let auto_settlement = match block_duration.map(|block_duration| {
+        schedule_settlement(
+            nft_contract_id,
+            token_id,
+            ended_at,
+            AUTO_SETTLEMENT_GAS,
+            block_duration,
+        )
+    }) {
+        Some(Ok(auto_settlement)) => Some(auto_settlement),
+        Some(Err(error)) => {
+            item.tx = None;
+            return Err(error);
+        }
+        None => None,
+    };
+
     if nft_transfer(tx_id, nft_contract_id, &exec::program_id(), token_id)
         .await
         .is_err()
@@ -325,10 +584,16 @@ async fn create_auction_tx(
     item.ft_contract_id = ft_contract_id;
     item.auction = Some(Auction {
         bid_period,
-        started_at: exec::block_timestamp(),
-        ended_at: exec::block_timestamp() + duration,
+        started_at,
+        ended_at,
+        max_ended_at: ended_at.saturating_add(max_extension),
         current_price: price,
         current_winner: ActorId::zero(),
+        reserve_price_commitment,
+        reserve_price: None,
+        buy_now_price,
+        bid_increment,
+        auto_settlement,
     });
     item.tx = None;
     Ok(MarketEvent::AuctionCreated {
@@ -348,8 +613,9 @@ async fn add_bid_tx(
     price: Price,
 ) -> Result<MarketEvent, MarketErr> {
     let auction: &mut Auction = item.auction.as_mut().expect("Can't be None");
-    if price <= auction.current_price {
-        return Err(MarketErr::WrongPrice);
+    if let Err(error) = check_bid_price(auction, price) {
+        item.tx = None;
+        return Err(error);
     }
 
     if transfer_tokens(tx_id, ft_contract_id, account, &exec::program_id(), price)
@@ -379,11 +645,136 @@ async fn add_bid_tx(
     auction.current_price = price;
     auction.current_winner = *account;
 
-    Ok(MarketEvent::BidAdded {
-        nft_contract_id: *nft_contract_id,
+    Ok(bid_event(
+        nft_contract_id,
         token_id,
         price,
-    })
+        extend_auction(auction),
+    ))
+}
+
+/// Checks that the bid exceeds the current price of the auction.
+/// If the auction already has a winner, the bid must also
+/// exceed the current price at least by the auction's bid increment.
+/// The bid of the buy-now price isn't subject to the bid increment.
+fn check_bid_price(auction: &Auction, price: Price) -> Result<(), MarketErr> {
+    if price <= auction.current_price {
+        return Err(MarketErr::WrongPrice);
+    }
+
+    if auction.current_winner.is_zero() || auction.buy_now_price == Some(price) {
+        return Ok(());
+    }
+
+    let BidIncrement {
+        absolute,
+        basis_points,
+    } = auction.bid_increment;
+    let relative = basis_points_share(auction.current_price, basis_points);
+    if price - auction.current_price < absolute.max(relative) {
+        return Err(MarketErr::BidIncrementTooLow);
+    }
+
+    Ok(())
+}
+
+/// Extends the auction up to `bid_period` after the current block
+/// if a bid is made within the last `bid_period` of a running auction.
+/// The auction is never extended beyond its `max_ended_at`.
+///
+/// The automatic settlement isn't rescheduled here, it's rescheduled
+/// when the message arrives before the new end of the auction.
+///
+/// Returns the new end of the auction if it was extended.
+fn extend_auction(auction: &mut Auction) -> Option<u64> {
+    let now = exec::block_timestamp();
+    if auction.ended_at < now {
+        return None;
+    }
+
+    let new_end = now
+        .saturating_add(auction.bid_period)
+        .min(auction.max_ended_at);
+    if new_end <= auction.ended_at {
+        return None;
+    }
+
+    auction.ended_at = new_end;
+    Some(new_end)
+}
+
+/// Estimates the block duration from the blocks passed since the marketplace initialization.
+fn block_duration(market: &Market) -> u64 {
+    let (height, timestamp) = market.initialized_at;
+    let blocks = exec::block_height().saturating_sub(height);
+    if blocks == 0 {
+        return MIN_BLOCK_DURATION;
+    }
+
+    (exec::block_timestamp().saturating_sub(timestamp) / blocks as u64).max(1)
+}
+
+/// Sends the delayed [`MarketAction::SettleAuction`] message to the marketplace
+/// that is dispatched in the estimated first block after `ended_at`.
+///
+/// The `gas` for the settlement is reserved from the current message.
+/// Previously scheduled messages can't be cancelled, so they just fail
+/// if the auction was already settled.
+fn schedule_settlement(
+    nft_contract_id: &ContractId,
+    token_id: TokenId,
+    ended_at: u64,
+    gas: u64,
+    block_duration: u64,
+) -> Result<AutoSettlement, MarketErr> {
+    let remaining = ended_at.saturating_sub(exec::block_timestamp());
+    let delay = u32::try_from(remaining / block_duration + 1)
+        .map_err(|_| MarketErr::AutoSettlementNotScheduled)?;
+
+    let reservation_id = ReservationId::reserve(gas, delay + 1)
+        .map_err(|_| MarketErr::AutoSettlementNotScheduled)?;
+    match msg::send_delayed_from_reservation(
+        reservation_id,
+        exec::program_id(),
+        MarketAction::SettleAuction {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+        },
+        0,
+        delay,
+    ) {
+        Ok(message_id) => Ok(AutoSettlement {
+            message_id,
+            block: exec::block_height().saturating_add(delay),
+            reserved_gas: gas,
+        }),
+        Err(_) => {
+            reservation_id.unreserve().ok();
+            Err(MarketErr::AutoSettlementNotScheduled)
+        }
+    }
+}
+
+fn bid_event(
+    nft_contract_id: &ContractId,
+    token_id: TokenId,
+    price: Price,
+    new_end: Option<u64>,
+) -> MarketEvent {
+    if let Some(new_end) = new_end {
+        MarketEvent::AuctionExtended {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            price,
+            new_end,
+        }
+    } else {
+        MarketEvent::BidAdded {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            price,
+        }
+    }
 }
 
 async fn settle_auction_tx(
@@ -395,14 +786,14 @@ async fn settle_auction_tx(
     price: Price,
 ) -> Result<MarketEvent, MarketErr> {
     let auction: &mut Auction = item.auction.as_mut().expect("Can't be None");
-    let winner = if auction.current_winner.is_zero() {
-        item.auction = None;
-        item.tx = None;
-
-        return Ok(MarketEvent::AuctionCancelled {
-            nft_contract_id: *nft_contract_id,
-            token_id,
-        });
+    // the bid for the buy-now price is accepted regardless of the reserve price
+    let reserve_price_not_reached = auction.reserve_price_commitment.is_some()
+        && auction.buy_now_price != Some(auction.current_price)
+        && auction
+            .reserve_price
+            .map_or(true, |reserve_price| auction.current_price < reserve_price);
+    let winner = if auction.current_winner.is_zero() || reserve_price_not_reached {
+        return cancel_auction_tx(tx_id, item, nft_contract_id, token_id).await;
     } else {
         auction.current_winner
     };
@@ -445,3 +836,54 @@ async fn settle_auction_tx(
         price,
     })
 }
+
+/// Cancels the auction, returns the leading bid to the bidder
+/// and the NFT to the owner.
+async fn cancel_auction_tx(
+    tx_id: TransactionId,
+    item: &mut Item,
+    nft_contract_id: &ContractId,
+    token_id: TokenId,
+) -> Result<MarketEvent, MarketErr> {
+    let auction: &mut Auction = item.auction.as_mut().expect("Can't be None");
+
+    if !auction.current_winner.is_zero() {
+        if let Some(ft_id) = item.ft_contract_id {
+            if transfer_tokens(
+                tx_id,
+                &ft_id,
+                &exec::program_id(),
+                &auction.current_winner,
+                auction.current_price,
+            )
+            .await
+            .is_err()
+            {
+                return Err(MarketErr::RerunTransaction);
+            }
+        } else {
+            msg::send(
+                auction.current_winner,
+                MarketEvent::TransferValue,
+                auction.current_price,
+            )
+            .expect("Error in sending value");
+        }
+        auction.current_winner = ActorId::zero();
+    }
+
+    if nft_transfer(tx_id, nft_contract_id, &item.owner, token_id)
+        .await
+        .is_err()
+    {
+        return Err(MarketErr::RerunTransaction);
+    }
+
+    item.auction = None;
+    item.tx = None;
+
+    Ok(MarketEvent::AuctionCancelled {
+        nft_contract_id: *nft_contract_id,
+        token_id,
+    })
+}
@@ -0,0 +1,404 @@
+use crate::{
+    contract::{refund_value, MarketHandler, MINIMUM_VALUE},
+    fees::{sale_payouts, FeeHandler},
+    nft_messages::*,
+    payment::*,
+    trade::{check_tokens, set_progress},
+};
+use gstd::{exec, msg, prelude::*, ActorId};
+use market_io::{
+    Bundle, BundleId, ContractId, Market, MarketErr, MarketEvent, MarketTx, Price, TokenId,
+    TradeProgress, TransactionId,
+};
+
+#[async_trait::async_trait]
+pub trait BundleHandler {
+    async fn add_bundle(
+        &mut self,
+        tokens: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    fn remove_bundle(&mut self, bundle_id: BundleId) -> Result<MarketEvent, MarketErr>;
+
+    async fn buy_bundle(&mut self, bundle_id: BundleId) -> Result<MarketEvent, MarketErr>;
+}
+
+#[async_trait::async_trait]
+impl BundleHandler for Market {
+    async fn add_bundle(
+        &mut self,
+        tokens: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_approved_ft_contract(ft_contract_id);
+
+        if tokens.is_empty() || tokens.iter().collect::<BTreeSet<_>>().len() != tokens.len() {
+            return Err(MarketErr::InvalidBundle);
+        }
+
+        #[allow(clippy::absurd_extreme_comparisons)]
+        if ft_contract_id.is_some() && price <= 0
+            || ft_contract_id.is_none() && price <= MINIMUM_VALUE.into()
+        {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        let owner = msg::source();
+        for (nft_contract_id, token_id) in tokens.iter() {
+            self.check_approved_nft_contract(nft_contract_id);
+            if let Some(item) = self.items.get(&(*nft_contract_id, *token_id)) {
+                if item.auction.is_some() || item.sealed_auction.is_some() {
+                    return Err(MarketErr::ItemOnAuction);
+                }
+            }
+            if get_owner(nft_contract_id, *token_id).await != owner {
+                return Err(MarketErr::InvalidCaller);
+            }
+        }
+
+        let bundle_id = self.bundle_id;
+        self.bundle_id = self.bundle_id.wrapping_add(1);
+        self.bundles.insert(
+            bundle_id,
+            Bundle {
+                owner,
+                tokens,
+                ft_contract_id,
+                price,
+                tx: None,
+            },
+        );
+
+        Ok(MarketEvent::BundleAdded {
+            bundle_id,
+            owner,
+            price,
+        })
+    }
+
+    fn remove_bundle(&mut self, bundle_id: BundleId) -> Result<MarketEvent, MarketErr> {
+        let bundle = self
+            .bundles
+            .get(&bundle_id)
+            .ok_or(MarketErr::BundleDoesNotExists)?;
+
+        if bundle.owner != msg::source() {
+            return Err(MarketErr::InvalidCaller);
+        }
+
+        if bundle.tx.is_some() {
+            return Err(MarketErr::WrongTransaction);
+        }
+
+        self.bundles.remove(&bundle_id);
+
+        Ok(MarketEvent::BundleRemoved { bundle_id })
+    }
+
+    async fn buy_bundle(&mut self, bundle_id: BundleId) -> Result<MarketEvent, MarketErr> {
+        if let Some(bundle) = self
+            .bundles
+            .get(&bundle_id)
+            .filter(|bundle| bundle.tx.is_none())
+        {
+            let result = match bundle.tokens.iter().try_for_each(|(nft_contract_id, _)| {
+                self.check_trading_allowed(nft_contract_id, bundle.ft_contract_id)
+            }) {
+                // the NFTs could be put on an auction or transferred after listing
+                Ok(()) => check_tokens(self, &bundle.tokens, &bundle.owner).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                refund_value();
+                return Err(error);
+            }
+        }
+
+        // the rerun pays the treasuries of the transaction start
+        let treasuries: BTreeMap<ContractId, (ActorId, u16)> = self
+            .bundles
+            .get(&bundle_id)
+            .map(|bundle| match &bundle.tx {
+                Some((_, MarketTx::BundleSale { treasuries, .. })) => treasuries.clone(),
+                _ => bundle
+                    .tokens
+                    .iter()
+                    .map(|(nft_contract_id, _)| (*nft_contract_id, self.treasury(nft_contract_id)))
+                    .collect(),
+            })
+            .unwrap_or_default();
+
+        let bundle = self
+            .bundles
+            .get_mut(&bundle_id)
+            .ok_or(MarketErr::BundleDoesNotExists)?;
+
+        // payouts for the bundle sale (includes treasury, royalty accounts and seller)
+        let payouts =
+            bundle_payouts(&bundle.tokens, &bundle.owner, bundle.price, &treasuries).await;
+
+        let (tx_id, buyer) = if let Some((tx_id, tx)) = &bundle.tx {
+            match tx {
+                MarketTx::BundleSale { buyer, .. } if *buyer == msg::source() => (*tx_id, *buyer),
+                _ => return Err(MarketErr::WrongTransaction),
+            }
+        } else {
+            let buyer = msg::source();
+            if bundle.ft_contract_id.is_none() && msg::value() != bundle.price {
+                return Err(MarketErr::WrongPrice);
+            }
+
+            // each NFT can be transferred three times: to the marketplace, back to the seller and to the buyer
+            let tx_id = self.tx_id;
+            self.tx_id = self
+                .tx_id
+                .wrapping_add(3 * bundle.tokens.len() as u64 + payouts.len() as u64 + 1);
+            bundle.tx = Some((
+                tx_id,
+                MarketTx::BundleSale {
+                    buyer,
+                    price: bundle.price,
+                    progress: TradeProgress::Locked(0),
+                    treasuries,
+                },
+            ));
+            (tx_id, buyer)
+        };
+
+        let result = buy_bundle_tx(tx_id, bundle_id, bundle, &payouts).await;
+
+        if result.is_ok() {
+            let bundle = self.bundles.remove(&bundle_id).expect("Can't be None");
+            for contract_and_token_id in bundle.tokens.iter() {
+                if let Some(item) = self.items.get_mut(contract_and_token_id) {
+                    item.owner = buyer;
+                    item.price = None;
+                    item.expires_at = None;
+                    item.dutch_auction = None;
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Splits the price equally between the NFTs of the bundle
+/// and sums up the payouts of their contracts, each contract is charged its treasury fee.
+async fn bundle_payouts(
+    tokens: &[(ContractId, TokenId)],
+    owner: &ActorId,
+    price: Price,
+    treasuries: &BTreeMap<ContractId, (ActorId, u16)>,
+) -> Payout {
+    let mut tokens_per_contract: BTreeMap<ContractId, u128> = BTreeMap::new();
+    for (nft_contract_id, _) in tokens.iter() {
+        *tokens_per_contract.entry(*nft_contract_id).or_default() += 1;
+    }
+
+    let share = price / tokens.len() as u128;
+    // the remainder of the division goes to the first contract
+    let mut remainder = price - share * tokens.len() as u128;
+
+    let mut bundle_payouts = Payout::new();
+    for (nft_contract_id, count) in tokens_per_contract {
+        let price = share * count + remainder;
+        let treasury = treasuries[&nft_contract_id];
+        for (account, amount) in sale_payouts(&nft_contract_id, owner, price, treasury).await {
+            *bundle_payouts.entry(account).or_default() += amount;
+        }
+        remainder = 0;
+    }
+
+    bundle_payouts
+}
+
+/// Sells the bundle in steps recorded in the transaction,
+/// so the rerun continues from the step at which the transaction stopped.
+///
+/// Transaction ids of the steps for `n` NFTs:
+/// * `tx_id..tx_id + n` - transfers of NFTs to the marketplace account
+/// * `tx_id + n` - the payment in fungible tokens
+/// * `tx_id + n + 1..tx_id + 2n + 1` - returns of NFTs to the seller
+/// * `tx_id + 2n + 1..` - payouts and then transfers of NFTs to the buyer
+async fn buy_bundle_tx(
+    tx_id: TransactionId,
+    bundle_id: BundleId,
+    bundle: &mut Bundle,
+    payouts: &Payout,
+) -> Result<MarketEvent, MarketErr> {
+    let Some((_, MarketTx::BundleSale { buyer, price, mut progress, .. })) = bundle.tx.clone() else {
+        return Err(MarketErr::WrongTransaction);
+    };
+    let tokens_count = bundle.tokens.len() as u64;
+
+    if let TradeProgress::Locked(locked) = progress {
+        // transfer NFTs to the marketplace account
+        for (i, (nft_contract_id, token_id)) in
+            bundle.tokens.iter().enumerate().skip(locked as usize)
+        {
+            if nft_transfer(
+                tx_id.wrapping_add(i as u64),
+                nft_contract_id,
+                &exec::program_id(),
+                *token_id,
+            )
+            .await
+            .is_err()
+            {
+                progress = TradeProgress::Reverted {
+                    locked: i as u32,
+                    returned: 0,
+                };
+                break;
+            }
+            progress = TradeProgress::Locked(i as u32 + 1);
+            set_progress(&mut bundle.tx, progress);
+        }
+
+        if progress == TradeProgress::Locked(tokens_count as u32) {
+            // the native value is attached to the message that starts the transaction
+            progress = match bundle.ft_contract_id {
+                Some(ft_id) => {
+                    match transfer_tokens(
+                        tx_id.wrapping_add(tokens_count),
+                        &ft_id,
+                        &buyer,
+                        &exec::program_id(),
+                        price,
+                    )
+                    .await
+                    {
+                        Ok(()) => TradeProgress::Paid,
+                        Err(()) => TradeProgress::Reverted {
+                            locked: tokens_count as u32,
+                            returned: 0,
+                        },
+                    }
+                }
+                None => TradeProgress::Paid,
+            };
+        }
+        set_progress(&mut bundle.tx, progress);
+    }
+
+    if let TradeProgress::Reverted { locked, returned } = progress {
+        // return NFTs to the seller
+        for (i, (nft_contract_id, token_id)) in bundle
+            .tokens
+            .iter()
+            .enumerate()
+            .take(locked as usize)
+            .skip(returned as usize)
+        {
+            if nft_transfer(
+                tx_id.wrapping_add(tokens_count + 1 + i as u64),
+                nft_contract_id,
+                &bundle.owner,
+                *token_id,
+            )
+            .await
+            .is_err()
+            {
+                return Err(MarketErr::RerunTransaction);
+            }
+            set_progress(
+                &mut bundle.tx,
+                TradeProgress::Reverted {
+                    locked,
+                    returned: i as u32 + 1,
+                },
+            );
+        }
+
+        if bundle.ft_contract_id.is_none() {
+            msg::send(buyer, MarketEvent::TransferValue, price).expect("Error in sending value");
+        }
+        bundle.tx = None;
+
+        // all NFTs are locked only if the buyer failed to pay
+        return if locked as u64 == tokens_count {
+            Err(MarketErr::TokenTransferFailed)
+        } else {
+            Err(MarketErr::NFTTransferFailed)
+        };
+    }
+
+    // the native payouts that are too small to be sent are paid to the seller
+    let mut payouts = payouts.clone();
+    if bundle.ft_contract_id.is_none() {
+        let small_payouts: Vec<ActorId> = payouts
+            .iter()
+            .filter(|(account, amount)| {
+                **account != bundle.owner
+                    && **account != exec::program_id()
+                    && **amount <= MINIMUM_VALUE.into()
+            })
+            .map(|(account, _)| *account)
+            .collect();
+        for account in small_payouts {
+            let amount = payouts.remove(&account).unwrap_or_default();
+            *payouts.entry(bundle.owner).or_default() += amount;
+        }
+    }
+
+    let mut payout_tx_id = tx_id.wrapping_add(2 * tokens_count + 1);
+    if progress == TradeProgress::Paid {
+        // send tokens to the seller, royalties and tresuary account
+        // since tokens are on the marketplace account, the error can be only due the lack of gas
+        for (account, amount) in payouts.iter() {
+            match bundle.ft_contract_id {
+                Some(ft_id) => {
+                    if transfer_tokens(payout_tx_id, &ft_id, &exec::program_id(), account, *amount)
+                        .await
+                        .is_err()
+                    {
+                        return Err(MarketErr::RerunTransaction);
+                    }
+                }
+                None => {
+                    if account != &exec::program_id() && *amount > MINIMUM_VALUE.into() {
+                        msg::send(*account, MarketEvent::TransferValue, *amount)
+                            .expect("Error in sending value");
+                    }
+                }
+            }
+            payout_tx_id = payout_tx_id.wrapping_add(1);
+        }
+        progress = TradeProgress::Delivered(0);
+        set_progress(&mut bundle.tx, progress);
+    } else {
+        payout_tx_id = payout_tx_id.wrapping_add(payouts.len() as u64);
+    }
+
+    if let TradeProgress::Delivered(delivered) = progress {
+        // transfer NFTs to the buyer
+        for (i, (nft_contract_id, token_id)) in
+            bundle.tokens.iter().enumerate().skip(delivered as usize)
+        {
+            if nft_transfer(
+                payout_tx_id.wrapping_add(i as u64),
+                nft_contract_id,
+                &buyer,
+                *token_id,
+            )
+            .await
+            .is_err()
+            {
+                return Err(MarketErr::RerunTransaction);
+            }
+            set_progress(&mut bundle.tx, TradeProgress::Delivered(i as u32 + 1));
+        }
+    }
+
+    bundle.tx = None;
+
+    Ok(MarketEvent::BundleSold {
+        bundle_id,
+        owner: buyer,
+        price,
+    })
+}
@@ -0,0 +1,493 @@
+use crate::{
+    contract::{change_owner, MarketHandler, MINIMUM_VALUE},
+    fees::{sale_payouts, FeeHandler},
+    nft_messages::{get_attributes, get_owner},
+    offers::{accept_offer_tx, is_expired},
+    payment::transfer_tokens,
+};
+use gstd::{exec, msg, prelude::*, ActorId};
+use market_io::*;
+
+#[async_trait::async_trait]
+pub trait CollectionOffersHandler {
+    async fn add_collection_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        expires_at: Option<u64>,
+        attributes: Attributes,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn accept_collection_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        buyer: &ActorId,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn withdraw_collection_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn cleanup_expired_collection_offers(
+        &mut self,
+        nft_contract_id: &ContractId,
+    ) -> Result<MarketEvent, MarketErr>;
+}
+
+#[async_trait::async_trait]
+impl CollectionOffersHandler for Market {
+    async fn add_collection_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        expires_at: Option<u64>,
+        attributes: Attributes,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_approved_nft_contract(nft_contract_id);
+
+        if let Some(ft_contract_id) = &ft_contract_id {
+            if !self.approved_ft_contracts.contains(ft_contract_id) {
+                return Err(MarketErr::ContractNotApproved);
+            }
+        }
+
+        #[allow(clippy::absurd_extreme_comparisons)]
+        if ft_contract_id.is_some() && price <= 0
+            || ft_contract_id.is_none() && price <= MINIMUM_VALUE.into()
+        {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        if ft_contract_id.is_none() && msg::value() != price {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        if expires_at.map_or(false, |expires_at| expires_at <= exec::block_timestamp()) {
+            return Err(MarketErr::OfferExpired);
+        }
+
+        let account = msg::source();
+        let collection = self.collection_offers.entry(*nft_contract_id).or_default();
+
+        if let Some((tx_id, tx)) = collection.tx.get(&account).cloned() {
+            return match tx {
+                MarketTx::Offer {
+                    ft_id,
+                    price: pending_price,
+                    expires_at,
+                    attributes,
+                    ..
+                } if Some(ft_id) == ft_contract_id && pending_price == price => {
+                    add_collection_offer_tx(
+                        tx_id,
+                        collection,
+                        nft_contract_id,
+                        &ft_id,
+                        price,
+                        expires_at,
+                        attributes,
+                    )
+                    .await
+                }
+                _ => Err(MarketErr::WrongTransaction),
+            };
+        }
+
+        if collection
+            .offers
+            .contains_key(&(ft_contract_id, price, account))
+        {
+            return Err(MarketErr::OfferAlreadyExists);
+        }
+
+        let ft_id = if let Some(ft_id) = ft_contract_id {
+            ft_id
+        } else {
+            collection.offers.insert(
+                (None, price, account),
+                Offer {
+                    expires_at,
+                    counter_offer: None,
+                    attributes,
+                },
+            );
+            return Ok(MarketEvent::CollectionOfferAdded {
+                nft_contract_id: *nft_contract_id,
+                ft_contract_id,
+                price,
+            });
+        };
+
+        let tx_id = self.tx_id;
+        self.tx_id = self.tx_id.wrapping_add(1);
+        collection.tx.insert(
+            account,
+            (
+                tx_id,
+                MarketTx::Offer {
+                    ft_id,
+                    price,
+                    account,
+                    expires_at,
+                    attributes: attributes.clone(),
+                },
+            ),
+        );
+
+        add_collection_offer_tx(
+            tx_id,
+            collection,
+            nft_contract_id,
+            &ft_id,
+            price,
+            expires_at,
+            attributes,
+        )
+        .await
+    }
+
+    async fn accept_collection_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        buyer: &ActorId,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+        let offer_id = (ft_contract_id, price, *buyer);
+
+        let pending = match self
+            .items
+            .get(&contract_and_token_id)
+            .and_then(|item| item.tx.clone())
+        {
+            Some((
+                tx_id,
+                MarketTx::AcceptCollectionOffer {
+                    offer_id: pending_offer_id,
+                    offer,
+                    treasury,
+                },
+            )) if pending_offer_id == offer_id => Some((tx_id, offer, treasury)),
+            Some(_) => return Err(MarketErr::WrongTransaction),
+            None => None,
+        };
+
+        let (tx_id, offer, payouts) = if let Some((tx_id, offer, treasury)) = pending {
+            // the pending transaction can be completed after the removal of the contracts
+            let owner = self.items[&contract_and_token_id].owner;
+            if owner != msg::source() {
+                return Err(MarketErr::OfferShouldAcceptedByOwner);
+            }
+
+            // the rerun pays the treasury of the transaction start
+            let payouts = sale_payouts(nft_contract_id, &owner, price, treasury).await;
+            (tx_id, offer, payouts)
+        } else {
+            self.check_trading_allowed(nft_contract_id, ft_contract_id)?;
+
+            let offer = self
+                .collection_offers
+                .get(nft_contract_id)
+                .and_then(|collection| collection.offers.get(&offer_id))
+                .ok_or(MarketErr::OfferIsNotExists)?;
+            if offer
+                .expires_at
+                .map_or(false, |expires_at| expires_at < exec::block_timestamp())
+            {
+                return Err(MarketErr::OfferExpired);
+            }
+            let attributes = offer.attributes.clone();
+
+            let owner = get_owner(nft_contract_id, token_id).await;
+            if owner != msg::source() {
+                return Err(MarketErr::OfferShouldAcceptedByOwner);
+            }
+
+            if !attributes.is_empty() {
+                let token_attributes = get_attributes(nft_contract_id, token_id)
+                    .await
+                    .ok_or(MarketErr::AttributesNotMatched)?;
+                if !attributes
+                    .iter()
+                    .all(|attribute| token_attributes.contains(attribute))
+                {
+                    return Err(MarketErr::AttributesNotMatched);
+                }
+            }
+
+            let treasury = self.treasury(nft_contract_id);
+            // payouts for NFT sale (includes treasury, royalty accounts and seller)
+            let payouts = sale_payouts(nft_contract_id, &owner, price, treasury).await;
+
+            // the offer could be withdrawn or the item put on an auction during the owner and payouts queries
+            if !self
+                .collection_offers
+                .get(nft_contract_id)
+                .and_then(|collection| collection.offers.get(&offer_id))
+                .map_or(false, |offer| offer.attributes == attributes)
+            {
+                return Err(MarketErr::OfferIsNotExists);
+            }
+            let item = self
+                .items
+                .entry(contract_and_token_id)
+                .or_insert_with(|| Item {
+                    token_id,
+                    owner,
+                    ..Default::default()
+                });
+            if item.auction.is_some() || item.sealed_auction.is_some() {
+                return Err(MarketErr::AuctionIsOpened);
+            }
+            if item.tx.is_some() {
+                return Err(MarketErr::WrongTransaction);
+            }
+
+            // the offer is locked in the transaction until the NFT is sold
+            let offer = self
+                .collection_offers
+                .get_mut(nft_contract_id)
+                .and_then(|collection| collection.offers.remove(&offer_id))
+                .expect("Can't be None");
+            let tx_id = self.tx_id;
+            self.tx_id = self.tx_id.wrapping_add(payouts.len() as u64 + 1);
+            // the NFT is sold by the offer, so its listing is cancelled
+            change_owner(item, owner);
+            item.tx = Some((
+                tx_id,
+                MarketTx::AcceptCollectionOffer {
+                    offer_id,
+                    offer: offer.clone(),
+                    treasury,
+                },
+            ));
+            (tx_id, offer, payouts)
+        };
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .expect("Can't be None");
+
+        let result = accept_offer_tx(
+            tx_id,
+            item,
+            nft_contract_id,
+            ft_contract_id,
+            buyer,
+            token_id,
+            price,
+            &payouts,
+        )
+        .await;
+
+        // the NFT wasn't sold, so the offer is unlocked
+        if result.is_err() && item.tx.is_none() {
+            self.collection_offers
+                .entry(*nft_contract_id)
+                .or_default()
+                .offers
+                .insert(offer_id, offer);
+        }
+
+        result
+    }
+
+    async fn withdraw_collection_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        let account = msg::source();
+        let collection = self
+            .collection_offers
+            .get_mut(nft_contract_id)
+            .ok_or(MarketErr::OfferIsNotExists)?;
+
+        if !collection
+            .offers
+            .contains_key(&(ft_contract_id, price, account))
+        {
+            return Err(MarketErr::OfferIsNotExists);
+        }
+
+        let ft_id = if let Some(ft_id) = ft_contract_id {
+            ft_id
+        } else {
+            msg::send(account, MarketEvent::TransferValue, price).expect("Error in sending value");
+            collection.offers.remove(&(None, price, account));
+            return Ok(MarketEvent::CollectionOfferWithdrawn {
+                nft_contract_id: *nft_contract_id,
+                ft_contract_id,
+                price,
+            });
+        };
+
+        if let Some((tx_id, tx)) = collection.tx.get(&account).cloned() {
+            return match tx {
+                MarketTx::Withdraw {
+                    ft_id: pending_ft_id,
+                    price: pending_price,
+                    ..
+                } if pending_ft_id == ft_id && pending_price == price => {
+                    withdraw_collection_offer_tx(tx_id, collection, nft_contract_id, &ft_id, price)
+                        .await
+                }
+                _ => Err(MarketErr::WrongTransaction),
+            };
+        }
+
+        let tx_id = self.tx_id;
+        self.tx_id = self.tx_id.wrapping_add(1);
+        collection.tx.insert(
+            account,
+            (
+                tx_id,
+                MarketTx::Withdraw {
+                    ft_id,
+                    price,
+                    account,
+                },
+            ),
+        );
+
+        withdraw_collection_offer_tx(tx_id, collection, nft_contract_id, &ft_id, price).await
+    }
+
+    async fn cleanup_expired_collection_offers(
+        &mut self,
+        nft_contract_id: &ContractId,
+    ) -> Result<MarketEvent, MarketErr> {
+        let mut count = 0;
+        let Some(collection) = self.collection_offers.get_mut(nft_contract_id) else {
+            return Ok(MarketEvent::ExpiredCollectionOffersCleaned {
+                nft_contract_id: *nft_contract_id,
+                count,
+            });
+        };
+
+        // the offers of the accounts with the pending transactions are refunded after their completion
+        let expired_offers: Vec<_> = collection
+            .offers
+            .iter()
+            .filter(|(offer_id, offer)| {
+                is_expired(offer) && !collection.tx.contains_key(&offer_id.2)
+            })
+            .map(|(&offer_id, _)| offer_id)
+            .collect();
+
+        for offer_id in expired_offers {
+            let (ft_contract_id, price, account) = offer_id;
+
+            // the offer could be withdrawn by its creator during the previous refund
+            if !collection.offers.contains_key(&offer_id) || collection.tx.contains_key(&account) {
+                continue;
+            }
+
+            if let Some(ft_id) = ft_contract_id {
+                let tx_id = self.tx_id;
+                self.tx_id = self.tx_id.wrapping_add(1);
+                collection.tx.insert(
+                    account,
+                    (
+                        tx_id,
+                        MarketTx::Withdraw {
+                            ft_id,
+                            price,
+                            account,
+                        },
+                    ),
+                );
+
+                let result =
+                    transfer_tokens(tx_id, &ft_id, &exec::program_id(), &account, price).await;
+                collection.tx.remove(&account);
+
+                if result.is_err() {
+                    return Err(MarketErr::TokenTransferFailed);
+                }
+            } else {
+                msg::send(account, MarketEvent::TransferValue, price)
+                    .expect("Error in sending value");
+            }
+
+            collection.offers.remove(&offer_id);
+            count += 1;
+        }
+
+        Ok(MarketEvent::ExpiredCollectionOffersCleaned {
+            nft_contract_id: *nft_contract_id,
+            count,
+        })
+    }
+}
+
+async fn add_collection_offer_tx(
+    tx_id: TransactionId,
+    collection: &mut CollectionOffers,
+    nft_contract_id: &ContractId,
+    ft_contract_id: &ContractId,
+    price: Price,
+    expires_at: Option<u64>,
+    attributes: Attributes,
+) -> Result<MarketEvent, MarketErr> {
+    let account = msg::source();
+    let result = transfer_tokens(tx_id, ft_contract_id, &account, &exec::program_id(), price).await;
+    collection.tx.remove(&account);
+
+    if result.is_err() {
+        return Err(MarketErr::TokenTransferFailed);
+    }
+
+    collection.offers.insert(
+        (Some(*ft_contract_id), price, account),
+        Offer {
+            expires_at,
+            counter_offer: None,
+            attributes,
+        },
+    );
+
+    Ok(MarketEvent::CollectionOfferAdded {
+        nft_contract_id: *nft_contract_id,
+        ft_contract_id: Some(*ft_contract_id),
+        price,
+    })
+}
+
+async fn withdraw_collection_offer_tx(
+    tx_id: TransactionId,
+    collection: &mut CollectionOffers,
+    nft_contract_id: &ContractId,
+    ft_contract_id: &ContractId,
+    price: Price,
+) -> Result<MarketEvent, MarketErr> {
+    let account = msg::source();
+    let result = transfer_tokens(tx_id, ft_contract_id, &exec::program_id(), &account, price).await;
+    collection.tx.remove(&account);
+
+    if result.is_err() {
+        return Err(MarketErr::TokenTransferFailed);
+    }
+
+    collection
+        .offers
+        .remove(&(Some(*ft_contract_id), price, account));
+
+    Ok(MarketEvent::CollectionOfferWithdrawn {
+        nft_contract_id: *nft_contract_id,
+        ft_contract_id: Some(*ft_contract_id),
+        price,
+    })
+}
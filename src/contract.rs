@@ -1,10 +1,18 @@
-use crate::{auction::*, nft_messages::get_owner, offers::OffersHandler, sale::SaleHandler};
-use gstd::{errors::Result as GstdResult, msg, prelude::*, ActorId, MessageId};
+use crate::{
+    auction::*,
+    bundles::BundleHandler,
+    collection_offers::CollectionOffersHandler,
+    dutch_auction::DutchAuctionHandler,
+    fees::{is_valid_treasury_fee, FeeHandler},
+    nft_messages::{get_owner, get_owners},
+    offers::{refund_offers, OffersHandler},
+    sale::SaleHandler,
+    sealed_auction::SealedAuctionHandler,
+    swaps::SwapHandler,
+};
+use gstd::{errors::Result as GstdResult, exec, msg, prelude::*, ActorId, MessageId};
 use market_io::*;
 
-const MIN_TREASURY_FEE: u16 = 0;
-const MAX_TREASURT_FEE: u16 = 5;
-pub const BASE_PERCENT: u16 = 100;
 pub const MINIMUM_VALUE: u64 = 500;
 
 static mut MARKET: Option<Market> = None;
@@ -15,45 +23,193 @@ pub trait MarketHandler {
 
     fn add_ft_contract(&mut self, ft_contract_id: &ContractId) -> Result<MarketEvent, MarketErr>;
 
+    fn remove_nft_contract(
+        &mut self,
+        nft_contract_id: &ContractId,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    fn remove_ft_contract(&mut self, ft_contract_id: &ContractId)
+        -> Result<MarketEvent, MarketErr>;
+
+    fn pause(&mut self, nft_contract_id: Option<ContractId>) -> Result<MarketEvent, MarketErr>;
+
+    fn unpause(&mut self, nft_contract_id: Option<ContractId>) -> Result<MarketEvent, MarketErr>;
+
+    fn propose_admin(&mut self, admin_id: &ActorId) -> Result<MarketEvent, MarketErr>;
+
+    fn accept_admin(&mut self) -> Result<MarketEvent, MarketErr>;
+
+    fn grant_role(&mut self, account: &ActorId, role: Role) -> Result<MarketEvent, MarketErr>;
+
+    fn revoke_role(&mut self, account: &ActorId, role: Role) -> Result<MarketEvent, MarketErr>;
+
     async fn add_market_data(
         &mut self,
         nft_contract_id: &ContractId,
         ft_contract_id: Option<ContractId>,
         token_id: TokenId,
         price: Option<Price>,
+        expires_at: Option<u64>,
     ) -> Result<MarketEvent, MarketErr>;
 
-    fn check_admin(&self);
+    async fn batch_add_market_data(
+        &mut self,
+        market_data: Vec<MarketData>,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    fn batch_remove_listings(
+        &mut self,
+        tokens: Vec<(ContractId, TokenId)>,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn remove_item(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        offers_policy: OffersPolicy,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn sync_item(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    fn has_role(&self, account: &ActorId, role: Role) -> bool;
+
+    fn check_role(&self, role: Role) -> Result<(), MarketErr>;
 
     fn check_approved_nft_contract(&self, nft_contract_id: &ActorId);
 
     fn check_approved_ft_contract(&self, ft_contract_id: Option<ActorId>);
+
+    fn check_trading_allowed(
+        &self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+    ) -> Result<(), MarketErr>;
+
+    /// The trading of the item is frozen after the removal of its contracts,
+    /// but the pending transaction can still be completed.
+    fn check_item_trading_allowed(
+        &self,
+        contract_and_token_id: &(ContractId, TokenId),
+    ) -> Result<(), MarketErr>;
 }
 
 #[async_trait::async_trait]
 impl MarketHandler for Market {
     fn add_nft_contract(&mut self, nft_contract_id: &ContractId) -> Result<MarketEvent, MarketErr> {
-        self.check_admin();
+        self.check_role(Role::ContractCurator)?;
         self.approved_nft_contracts.insert(*nft_contract_id);
         Ok(MarketEvent::NftContractAdded(*nft_contract_id))
     }
 
     fn add_ft_contract(&mut self, ft_contract_id: &ContractId) -> Result<MarketEvent, MarketErr> {
-        self.check_admin();
+        self.check_role(Role::ContractCurator)?;
         self.approved_ft_contracts.insert(*ft_contract_id);
         Ok(MarketEvent::FtContractAdded(*ft_contract_id))
     }
 
+    fn remove_nft_contract(
+        &mut self,
+        nft_contract_id: &ContractId,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::ContractCurator)?;
+        self.approved_nft_contracts.remove(nft_contract_id);
+        Ok(MarketEvent::NftContractRemoved(*nft_contract_id))
+    }
+
+    fn remove_ft_contract(
+        &mut self,
+        ft_contract_id: &ContractId,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::ContractCurator)?;
+        self.approved_ft_contracts.remove(ft_contract_id);
+        Ok(MarketEvent::FtContractRemoved(*ft_contract_id))
+    }
+
+    fn pause(&mut self, nft_contract_id: Option<ContractId>) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::Pauser)?;
+        match nft_contract_id {
+            Some(nft_contract_id) => {
+                self.paused_nft_contracts.insert(nft_contract_id);
+            }
+            None => self.is_paused = true,
+        }
+        Ok(MarketEvent::Paused { nft_contract_id })
+    }
+
+    fn unpause(&mut self, nft_contract_id: Option<ContractId>) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::Pauser)?;
+        match nft_contract_id {
+            Some(nft_contract_id) => {
+                self.paused_nft_contracts.remove(&nft_contract_id);
+            }
+            None => self.is_paused = false,
+        }
+        Ok(MarketEvent::Unpaused { nft_contract_id })
+    }
+
+    fn propose_admin(&mut self, admin_id: &ActorId) -> Result<MarketEvent, MarketErr> {
+        if msg::source() != self.admin_id {
+            return Err(MarketErr::InvalidCaller);
+        }
+        self.pending_admin_id = Some(*admin_id);
+        Ok(MarketEvent::AdminProposed(*admin_id))
+    }
+
+    fn accept_admin(&mut self) -> Result<MarketEvent, MarketErr> {
+        if self.pending_admin_id != Some(msg::source()) {
+            return Err(MarketErr::InvalidCaller);
+        }
+        self.admin_id = msg::source();
+        self.pending_admin_id = None;
+        Ok(MarketEvent::AdminChanged(self.admin_id))
+    }
+
+    fn grant_role(&mut self, account: &ActorId, role: Role) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::Admin)?;
+        self.roles.entry(role).or_default().insert(*account);
+        Ok(MarketEvent::RoleGranted {
+            account: *account,
+            role,
+        })
+    }
+
+    fn revoke_role(&mut self, account: &ActorId, role: Role) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::Admin)?;
+        if let Some(accounts) = self.roles.get_mut(&role) {
+            accounts.remove(account);
+            if accounts.is_empty() {
+                self.roles.remove(&role);
+            }
+        }
+        Ok(MarketEvent::RoleRevoked {
+            account: *account,
+            role,
+        })
+    }
+
     async fn add_market_data(
         &mut self,
         nft_contract_id: &ContractId,
         ft_contract_id: Option<ContractId>,
         token_id: TokenId,
         price: Option<Price>,
+        expires_at: Option<u64>,
     ) -> Result<MarketEvent, MarketErr> {
         self.check_approved_nft_contract(nft_contract_id);
         self.check_approved_ft_contract(ft_contract_id);
-        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let data = MarketData {
+            nft_contract_id: *nft_contract_id,
+            ft_contract_id,
+            token_id,
+            price,
+            expires_at,
+        };
+        check_market_data(self, &data)?;
 
         let owner = get_owner(nft_contract_id, token_id).await;
         assert_eq!(
@@ -61,21 +217,10 @@ impl MarketHandler for Market {
             msg::source(),
             "Only owner has a right to add NFT to the marketplace"
         );
-        self.items
-            .entry(contract_and_token_id)
-            .and_modify(|item| {
-                item.price = price;
-                item.ft_contract_id = ft_contract_id
-            })
-            .or_insert(Item {
-                token_id,
-                owner,
-                ft_contract_id,
-                price,
-                auction: None,
-                offers: BTreeMap::new(),
-                tx: None,
-            });
+
+        // the item could be put on an auction during the owner query
+        check_market_data(self, &data)?;
+        list_item(&mut self.items, owner, &data);
 
         Ok(MarketEvent::MarketDataAdded {
             nft_contract_id: *nft_contract_id,
@@ -84,10 +229,181 @@ impl MarketHandler for Market {
         })
     }
 
-    fn check_admin(&self) {
-        if msg::source() != self.admin_id {
-            panic!("Only owner can make that action");
+    async fn batch_add_market_data(
+        &mut self,
+        market_data: Vec<MarketData>,
+    ) -> Result<MarketEvent, MarketErr> {
+        let mut results: Vec<Result<(), MarketErr>> = market_data
+            .iter()
+            .map(|data| check_market_data(self, data))
+            .collect();
+
+        // the owners are requested only for the items that passed the checks
+        let tokens: Vec<(ContractId, TokenId)> = market_data
+            .iter()
+            .zip(results.iter())
+            .filter(|(_, result)| result.is_ok())
+            .map(|(data, _)| (data.nft_contract_id, data.token_id))
+            .collect();
+        let mut owners = get_owners(&tokens).await.into_iter();
+
+        for (data, result) in market_data.iter().zip(results.iter_mut()) {
+            if result.is_err() {
+                continue;
+            }
+            let owner = owners.next().expect("Can't be None");
+            if owner != msg::source() {
+                *result = Err(MarketErr::InvalidCaller);
+                continue;
+            }
+            // the item could be put on an auction during the owners query
+            if let Err(error) = check_market_data(self, data) {
+                *result = Err(error);
+                continue;
+            }
+            list_item(&mut self.items, owner, data);
         }
+
+        Ok(MarketEvent::BatchResult(
+            market_data
+                .into_iter()
+                .zip(results)
+                .map(|(data, result)| (data.nft_contract_id, data.token_id, result))
+                .collect(),
+        ))
+    }
+
+    fn batch_remove_listings(
+        &mut self,
+        tokens: Vec<(ContractId, TokenId)>,
+    ) -> Result<MarketEvent, MarketErr> {
+        let results = tokens
+            .into_iter()
+            .map(|contract_and_token_id| {
+                let result = match self.items.get_mut(&contract_and_token_id) {
+                    None => Err(MarketErr::ItemDoesNotExists),
+                    Some(item) if item.owner != msg::source() => Err(MarketErr::InvalidCaller),
+                    Some(item) if item.auction.is_some() || item.sealed_auction.is_some() => {
+                        Err(MarketErr::ItemOnAuction)
+                    }
+                    Some(item) if item.price.is_none() && item.dutch_auction.is_none() => {
+                        Err(MarketErr::ItemIsNotOnSale)
+                    }
+                    Some(item) => {
+                        item.price = None;
+                        item.expires_at = None;
+                        item.dutch_auction = None;
+                        Ok(())
+                    }
+                };
+                (contract_and_token_id.0, contract_and_token_id.1, result)
+            })
+            .collect();
+
+        Ok(MarketEvent::BatchResult(results))
+    }
+
+    async fn remove_item(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        offers_policy: OffersPolicy,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        if item.owner != msg::source() {
+            return Err(MarketErr::InvalidCaller);
+        }
+
+        if item.auction.is_some() || item.sealed_auction.is_some() {
+            return Err(MarketErr::ItemOnAuction);
+        }
+
+        if item.tx.is_some() {
+            return Err(MarketErr::WrongTransaction);
+        }
+
+        match offers_policy {
+            OffersPolicy::Refund => {
+                let offer_ids: Vec<OfferId> = item.offers.keys().copied().collect();
+                refund_offers(&mut self.tx_id, item, nft_contract_id, token_id, &offer_ids).await?;
+            }
+            OffersPolicy::Keep if !item.offers.is_empty() => {
+                item.ft_contract_id = None;
+                item.price = None;
+                item.expires_at = None;
+                item.dutch_auction = None;
+
+                return Ok(MarketEvent::ItemRemoved {
+                    nft_contract_id: *nft_contract_id,
+                    token_id,
+                });
+            }
+            OffersPolicy::Keep => {}
+        }
+
+        self.items.remove(&contract_and_token_id);
+
+        Ok(MarketEvent::ItemRemoved {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+        })
+    }
+
+    async fn sync_item(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        // the NFT is held by the marketplace during the auction
+        if item.auction.is_some() || item.sealed_auction.is_some() {
+            return Err(MarketErr::ItemOnAuction);
+        }
+
+        if item.tx.is_some() {
+            return Err(MarketErr::WrongTransaction);
+        }
+
+        let owner = get_owner(nft_contract_id, token_id).await;
+        let is_changed = owner != item.owner;
+        if is_changed {
+            change_owner(item, owner);
+        }
+
+        Ok(MarketEvent::ItemSynced {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            owner,
+            is_changed,
+        })
+    }
+
+    fn has_role(&self, account: &ActorId, role: Role) -> bool {
+        let has = |role| {
+            self.roles
+                .get(&role)
+                .map_or(false, |accounts| accounts.contains(account))
+        };
+        *account == self.admin_id || has(Role::Admin) || has(role)
+    }
+
+    fn check_role(&self, role: Role) -> Result<(), MarketErr> {
+        if !self.has_role(&msg::source(), role) {
+            return Err(MarketErr::MissingRole);
+        }
+        Ok(())
     }
 
     fn check_approved_nft_contract(&self, nft_contract_id: &ActorId) {
@@ -105,37 +421,385 @@ impl MarketHandler for Market {
             panic!("that ft contract is not approved");
         }
     }
+
+    fn check_trading_allowed(
+        &self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+    ) -> Result<(), MarketErr> {
+        if !self.approved_nft_contracts.contains(nft_contract_id)
+            || ft_contract_id.map_or(false, |ft_contract_id| {
+                !self.approved_ft_contracts.contains(&ft_contract_id)
+            })
+        {
+            return Err(MarketErr::ContractNotApproved);
+        }
+        Ok(())
+    }
+
+    fn check_item_trading_allowed(
+        &self,
+        contract_and_token_id: &(ContractId, TokenId),
+    ) -> Result<(), MarketErr> {
+        match self.items.get(contract_and_token_id) {
+            Some(item) if item.tx.is_none() => {
+                self.check_trading_allowed(&contract_and_token_id.0, item.ft_contract_id)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Rejects the new listings, auctions, bids, offers and purchases if the trading is paused
+/// on the marketplace or of the NFT contracts of the action.
+/// The actions that continue the pending transactions are allowed to complete them.
+fn check_paused(market: &Market, action: &MarketAction) -> Result<(), MarketErr> {
+    let is_new = |nft_contract_id: &ContractId, token_id: &TokenId| {
+        market
+            .items
+            .get(&(*nft_contract_id, *token_id))
+            .map_or(true, |item| item.tx.is_none())
+    };
+
+    let nft_contract_ids: Vec<ContractId> = match action {
+        MarketAction::AddMarketData {
+            nft_contract_id, ..
+        }
+        | MarketAction::AddOffer {
+            nft_contract_id, ..
+        }
+        | MarketAction::AddCollectionOffer {
+            nft_contract_id, ..
+        }
+        | MarketAction::CreateAuction {
+            nft_contract_id, ..
+        }
+        | MarketAction::CreateDutchAuction {
+            nft_contract_id, ..
+        }
+        | MarketAction::CreateSealedAuction {
+            nft_contract_id, ..
+        } => vec![*nft_contract_id],
+        MarketAction::BuyItem {
+            nft_contract_id,
+            token_id,
+        }
+        | MarketAction::AcceptOffer {
+            nft_contract_id,
+            token_id,
+            ..
+        }
+        | MarketAction::AcceptCollectionOffer {
+            nft_contract_id,
+            token_id,
+            ..
+        }
+        | MarketAction::CounterOffer {
+            nft_contract_id,
+            token_id,
+            ..
+        }
+        | MarketAction::AcceptCounterOffer {
+            nft_contract_id,
+            token_id,
+            ..
+        }
+        | MarketAction::CommitBid {
+            nft_contract_id,
+            token_id,
+            ..
+        }
+        | MarketAction::AddBid {
+            nft_contract_id,
+            token_id,
+            ..
+        }
+        | MarketAction::BuyNow {
+            nft_contract_id,
+            token_id,
+        } => {
+            if !is_new(nft_contract_id, token_id) {
+                return Ok(());
+            }
+            vec![*nft_contract_id]
+        }
+        MarketAction::BatchAddMarketData(market_data) => market_data
+            .iter()
+            .map(|data| data.nft_contract_id)
+            .collect(),
+        MarketAction::BuyItems { items, .. } => {
+            let nft_contract_ids: Vec<ContractId> = items
+                .iter()
+                .filter(|(nft_contract_id, token_id)| is_new(nft_contract_id, token_id))
+                .map(|(nft_contract_id, _)| *nft_contract_id)
+                .collect();
+            if nft_contract_ids.is_empty() {
+                return Ok(());
+            }
+            nft_contract_ids
+        }
+        MarketAction::AddBundle { tokens, .. } => tokens
+            .iter()
+            .map(|(nft_contract_id, _)| *nft_contract_id)
+            .collect(),
+        MarketAction::BuyBundle { bundle_id } => match market.bundles.get(bundle_id) {
+            Some(bundle) if bundle.tx.is_none() => bundle
+                .tokens
+                .iter()
+                .map(|(nft_contract_id, _)| *nft_contract_id)
+                .collect(),
+            _ => return Ok(()),
+        },
+        MarketAction::ProposeSwap {
+            offered, requested, ..
+        } => offered
+            .iter()
+            .chain(requested.iter())
+            .map(|(nft_contract_id, _)| *nft_contract_id)
+            .collect(),
+        MarketAction::AcceptSwap { swap_id } => match market.swaps.get(swap_id) {
+            Some(swap) if swap.tx.is_none() => swap
+                .offered
+                .iter()
+                .chain(swap.requested.iter())
+                .map(|(nft_contract_id, _)| *nft_contract_id)
+                .collect(),
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+
+    if market.is_paused
+        || nft_contract_ids
+            .iter()
+            .any(|nft_contract_id| market.paused_nft_contracts.contains(nft_contract_id))
+    {
+        return Err(MarketErr::Paused);
+    }
+
+    Ok(())
+}
+
+/// Checks the listing data that doesn't require the NFT owner.
+fn check_market_data(market: &Market, data: &MarketData) -> Result<(), MarketErr> {
+    if !market
+        .approved_nft_contracts
+        .contains(&data.nft_contract_id)
+    {
+        return Err(MarketErr::ContractNotApproved);
+    }
+
+    if let Some(ft_contract_id) = &data.ft_contract_id {
+        if !market.approved_ft_contracts.contains(ft_contract_id) {
+            return Err(MarketErr::ContractNotApproved);
+        }
+    }
+
+    if data
+        .expires_at
+        .map_or(false, |expires_at| expires_at <= exec::block_timestamp())
+    {
+        return Err(MarketErr::ListingExpired);
+    }
+
+    if let Some(item) = market.items.get(&(data.nft_contract_id, data.token_id)) {
+        if item.auction.is_some() || item.sealed_auction.is_some() {
+            return Err(MarketErr::ItemOnAuction);
+        }
+    }
+
+    Ok(())
+}
+
+/// Puts the item on the sale or changes its price.
+fn list_item(items: &mut BTreeMap<(ContractId, TokenId), Item>, owner: ActorId, data: &MarketData) {
+    items
+        .entry((data.nft_contract_id, data.token_id))
+        .and_modify(|item| {
+            item.price = data.price;
+            item.expires_at = data.expires_at;
+            item.ft_contract_id = data.ft_contract_id;
+            item.dutch_auction = None;
+        })
+        .or_insert(Item {
+            token_id: data.token_id,
+            owner,
+            ft_contract_id: data.ft_contract_id,
+            price: data.price,
+            expires_at: data.expires_at,
+            auction: None,
+            dutch_auction: None,
+            sealed_auction: None,
+            offers: BTreeMap::new(),
+            tx: None,
+        });
+}
+
+/// Sets the new owner of the NFT transferred outside the marketplace
+/// and removes the item from the sale, since the new owner didn't list it.
+pub fn change_owner(item: &mut Item, owner: ActorId) {
+    item.owner = owner;
+    item.price = None;
+    item.expires_at = None;
+    item.dutch_auction = None;
+}
+
+/// Returns the value attached to the message to its sender.
+pub fn refund_value() {
+    if msg::value() > 0 {
+        msg::send(msg::source(), MarketEvent::TransferValue, msg::value())
+            .expect("Error in sending value");
+    }
 }
 
 #[gstd::async_main]
 async fn main() {
     let action: MarketAction = msg::load().expect("Could not load Action");
     let market: &mut Market = unsafe { MARKET.get_or_insert(Market::default()) };
+    market.apply_scheduled_fees();
+
+    if let Err(error) = check_paused(market, &action) {
+        // the attached value is returned as the action isn't executed
+        refund_value();
+        reply(Err::<MarketEvent, _>(error))
+            .expect("Failed to encode or reply with `Result<MarketEvent, MarketErr>`");
+        return;
+    }
+
     let result = match action {
         MarketAction::AddNftContract(nft_contract_id) => market.add_nft_contract(&nft_contract_id),
+        MarketAction::RemoveNftContract(nft_contract_id) => {
+            market.remove_nft_contract(&nft_contract_id)
+        }
+        MarketAction::RemoveFtContract(ft_contract_id) => {
+            market.remove_ft_contract(&ft_contract_id)
+        }
         MarketAction::AddFTContract(nft_contract_id) => market.add_ft_contract(&nft_contract_id),
+        MarketAction::UpdateTreasuryFee { fee, delay } => market.update_treasury_fee(fee, delay),
+        MarketAction::UpdateTreasury(treasury_id) => market.update_treasury(&treasury_id),
+        MarketAction::SetCollectionFee {
+            nft_contract_id,
+            fee,
+            delay,
+        } => market.set_collection_fee(&nft_contract_id, fee, delay),
+        MarketAction::Pause { nft_contract_id } => market.pause(nft_contract_id),
+        MarketAction::Unpause { nft_contract_id } => market.unpause(nft_contract_id),
+        MarketAction::ProposeAdmin(admin_id) => market.propose_admin(&admin_id),
+        MarketAction::AcceptAdmin => market.accept_admin(),
+        MarketAction::GrantRole { account, role } => market.grant_role(&account, role),
+        MarketAction::RevokeRole { account, role } => market.revoke_role(&account, role),
         MarketAction::AddMarketData {
             nft_contract_id,
             ft_contract_id,
             token_id,
             price,
+            expires_at,
         } => {
             market
-                .add_market_data(&nft_contract_id, ft_contract_id, token_id, price)
+                .add_market_data(
+                    &nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    price,
+                    expires_at,
+                )
+                .await
+        }
+        MarketAction::BatchAddMarketData(market_data) => {
+            market.batch_add_market_data(market_data).await
+        }
+        MarketAction::BatchRemoveListings(tokens) => market.batch_remove_listings(tokens),
+        MarketAction::SyncItem {
+            nft_contract_id,
+            token_id,
+        } => market.sync_item(&nft_contract_id, token_id).await,
+        MarketAction::RemoveItem {
+            nft_contract_id,
+            token_id,
+            offers_policy,
+        } => {
+            market
+                .remove_item(&nft_contract_id, token_id, offers_policy)
                 .await
         }
         MarketAction::BuyItem {
             nft_contract_id,
             token_id,
         } => market.buy_item(&nft_contract_id, token_id).await,
+        MarketAction::BuyItems {
+            items,
+            ft_contract_id,
+            max_total_price,
+        } => {
+            market
+                .buy_items(items, ft_contract_id, max_total_price)
+                .await
+        }
         MarketAction::AddOffer {
             nft_contract_id,
             ft_contract_id,
             token_id,
             price,
+            expires_at,
+        } => {
+            market
+                .add_offer(
+                    &nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    price,
+                    expires_at,
+                )
+                .await
+        }
+        MarketAction::AddCollectionOffer {
+            nft_contract_id,
+            ft_contract_id,
+            price,
+            expires_at,
+            attributes,
+        } => {
+            market
+                .add_collection_offer(
+                    &nft_contract_id,
+                    ft_contract_id,
+                    price,
+                    expires_at,
+                    attributes,
+                )
+                .await
+        }
+        MarketAction::AcceptCollectionOffer {
+            nft_contract_id,
+            token_id,
+            ft_contract_id,
+            price,
+            buyer,
         } => {
             market
-                .add_offer(&nft_contract_id, ft_contract_id, token_id, price)
+                .accept_collection_offer(&nft_contract_id, token_id, ft_contract_id, price, &buyer)
+                .await
+        }
+        MarketAction::WithdrawCollectionOffer {
+            nft_contract_id,
+            ft_contract_id,
+            price,
+        } => {
+            market
+                .withdraw_collection_offer(&nft_contract_id, ft_contract_id, price)
+                .await
+        }
+        MarketAction::CleanupExpiredOffers {
+            nft_contract_id,
+            token_id,
+        } => {
+            market
+                .cleanup_expired_offers(&nft_contract_id, token_id)
+                .await
+        }
+        MarketAction::CleanupExpiredCollectionOffers { nft_contract_id } => {
+            market
+                .cleanup_expired_collection_offers(&nft_contract_id)
                 .await
         }
         MarketAction::AcceptOffer {
@@ -143,11 +807,43 @@ async fn main() {
             token_id,
             ft_contract_id,
             price,
+            buyer,
+        } => {
+            market
+                .accept_offer(&nft_contract_id, token_id, ft_contract_id, price, &buyer)
+                .await
+        }
+        MarketAction::CounterOffer {
+            nft_contract_id,
+            token_id,
+            ft_contract_id,
+            price,
+            buyer,
+            counter_price,
+        } => market.counter_offer(
+            &nft_contract_id,
+            token_id,
+            ft_contract_id,
+            price,
+            &buyer,
+            counter_price,
+        ),
+        MarketAction::AcceptCounterOffer {
+            nft_contract_id,
+            token_id,
+            ft_contract_id,
+            price,
         } => {
             market
-                .accept_offer(&nft_contract_id, token_id, ft_contract_id, price)
+                .accept_counter_offer(&nft_contract_id, token_id, ft_contract_id, price)
                 .await
         }
+        MarketAction::DeclineCounterOffer {
+            nft_contract_id,
+            token_id,
+            ft_contract_id,
+            price,
+        } => market.decline_counter_offer(&nft_contract_id, token_id, ft_contract_id, price),
         MarketAction::Withdraw {
             nft_contract_id,
             ft_contract_id,
@@ -158,6 +854,26 @@ async fn main() {
                 .withdraw(&nft_contract_id, token_id, ft_contract_id, price)
                 .await
         }
+        MarketAction::AddBundle {
+            tokens,
+            ft_contract_id,
+            price,
+        } => market.add_bundle(tokens, ft_contract_id, price).await,
+        MarketAction::RemoveBundle { bundle_id } => market.remove_bundle(bundle_id),
+        MarketAction::BuyBundle { bundle_id } => market.buy_bundle(bundle_id).await,
+        MarketAction::ProposeSwap {
+            counterparty,
+            offered,
+            requested,
+            ft_contract_id,
+            payment,
+        } => {
+            market
+                .propose_swap(&counterparty, offered, requested, ft_contract_id, payment)
+                .await
+        }
+        MarketAction::AcceptSwap { swap_id } => market.accept_swap(swap_id).await,
+        MarketAction::CancelSwap { swap_id } => market.cancel_swap(swap_id),
         MarketAction::CreateAuction {
             nft_contract_id,
             ft_contract_id,
@@ -165,6 +881,11 @@ async fn main() {
             min_price,
             bid_period,
             duration,
+            max_extension,
+            reserve_price_commitment,
+            buy_now_price,
+            bid_increment,
+            auto_settlement,
         } => {
             market
                 .create_auction(
@@ -174,14 +895,99 @@ async fn main() {
                     min_price,
                     bid_period,
                     duration,
+                    max_extension,
+                    reserve_price_commitment,
+                    buy_now_price,
+                    bid_increment,
+                    auto_settlement,
+                )
+                .await
+        }
+        MarketAction::CreateDutchAuction {
+            nft_contract_id,
+            ft_contract_id,
+            token_id,
+            start_price,
+            floor_price,
+            decay,
+            duration,
+        } => {
+            market
+                .create_dutch_auction(
+                    &nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    start_price,
+                    floor_price,
+                    decay,
+                    duration,
+                )
+                .await
+        }
+        MarketAction::CreateSealedAuction {
+            nft_contract_id,
+            ft_contract_id,
+            token_id,
+            min_price,
+            pricing,
+            commit_duration,
+            reveal_duration,
+        } => {
+            market
+                .create_sealed_auction(
+                    &nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    min_price,
+                    pricing,
+                    commit_duration,
+                    reveal_duration,
                 )
                 .await
         }
+        MarketAction::CommitBid {
+            nft_contract_id,
+            token_id,
+            commitment,
+            deposit,
+        } => {
+            market
+                .commit_bid(&nft_contract_id, token_id, commitment, deposit)
+                .await
+        }
+        MarketAction::RevealBid {
+            nft_contract_id,
+            token_id,
+            price,
+            salt,
+        } => market.reveal_bid(&nft_contract_id, token_id, price, salt),
+        MarketAction::SettleSealedAuction {
+            nft_contract_id,
+            token_id,
+        } => {
+            market
+                .settle_sealed_auction(&nft_contract_id, token_id)
+                .await
+        }
         MarketAction::AddBid {
             nft_contract_id,
             token_id,
             price,
         } => market.add_bid(&nft_contract_id, token_id, price).await,
+        MarketAction::BuyNow {
+            nft_contract_id,
+            token_id,
+        } => market.buy_now(&nft_contract_id, token_id).await,
+        MarketAction::RevealReservePrice {
+            nft_contract_id,
+            token_id,
+            reserve_price,
+            salt,
+        } => market.reveal_reserve_price(&nft_contract_id, token_id, reserve_price, salt),
+        MarketAction::CancelAuction {
+            nft_contract_id,
+            token_id,
+        } => market.cancel_auction(&nft_contract_id, token_id).await,
         MarketAction::SettleAuction {
             nft_contract_id,
             token_id,
@@ -194,11 +1000,7 @@ async fn main() {
 extern "C" fn init() {
     let config: InitMarket = msg::load().expect("Unable to decode InitConfig");
 
-    // In case when `MIN_TREASURY_FEE` is zero, operator `<=` is not required,
-    // because this is minimum value. But if `MIN_TREASURY_FEE` could be changed later,
-    // usage of `==` operator can lead to unwanted errors or exploits
-    #[allow(clippy::absurd_extreme_comparisons)]
-    if config.treasury_fee <= MIN_TREASURY_FEE || config.treasury_fee > MAX_TREASURT_FEE {
+    if !is_valid_treasury_fee(config.treasury_fee) {
         panic!("Wrong treasury fee");
     }
 
@@ -206,6 +1008,7 @@ extern "C" fn init() {
         admin_id: config.admin_id,
         treasury_id: config.treasury_id,
         treasury_fee: config.treasury_fee,
+        initialized_at: (exec::block_height(), exec::block_timestamp()),
         ..Default::default()
     };
     unsafe { MARKET = Some(market) };
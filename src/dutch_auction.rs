@@ -0,0 +1,130 @@
+use crate::contract::MarketHandler;
+use gstd::{exec, msg, prelude::*};
+use market_io::*;
+use primitive_types::U256;
+
+const MIN_DURATION: u64 = 60_000;
+
+#[async_trait::async_trait]
+pub trait DutchAuctionHandler {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_dutch_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        token_id: TokenId,
+        start_price: Price,
+        floor_price: Price,
+        decay: PriceDecay,
+        duration: u64,
+    ) -> Result<MarketEvent, MarketErr>;
+}
+
+#[async_trait::async_trait]
+impl DutchAuctionHandler for Market {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_dutch_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        token_id: TokenId,
+        start_price: Price,
+        floor_price: Price,
+        decay: PriceDecay,
+        duration: u64,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_approved_nft_contract(nft_contract_id);
+        self.check_approved_ft_contract(ft_contract_id);
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        assert_eq!(
+            item.owner,
+            msg::source(),
+            "Only owner has a right to start the auction"
+        );
+
+        if item.auction.is_some() || item.sealed_auction.is_some() {
+            return Err(MarketErr::AuctionIsAlreadyExists);
+        }
+
+        clear_ended_dutch_auction(item);
+        if item.dutch_auction.is_some() {
+            return Err(MarketErr::AuctionIsAlreadyExists);
+        }
+
+        assert!(
+            item.price.is_none(),
+            "Remove the item from the sale before starting the auction"
+        );
+
+        if item.tx.is_some() {
+            return Err(MarketErr::WrongTransaction);
+        }
+
+        let step = match decay {
+            PriceDecay::Linear => 1,
+            PriceDecay::Stepwise { step } => step,
+        };
+        if duration < MIN_DURATION || step == 0 || step > duration {
+            return Err(MarketErr::AuctionBidPeriodOrDurationIsInvalid);
+        }
+
+        #[allow(clippy::absurd_extreme_comparisons)]
+        if floor_price <= 0 {
+            return Err(MarketErr::AuctionMinPriceIsZero);
+        }
+
+        if start_price < floor_price {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        item.ft_contract_id = ft_contract_id;
+        item.dutch_auction = Some(DutchAuction {
+            started_at: exec::block_timestamp(),
+            ended_at: exec::block_timestamp() + duration,
+            start_price,
+            floor_price,
+            decay,
+        });
+
+        Ok(MarketEvent::DutchAuctionCreated {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            start_price,
+            floor_price,
+        })
+    }
+}
+
+/// Removes the Dutch auction of the item that ended without a sale,
+/// so the item can be listed or put on an auction again.
+pub fn clear_ended_dutch_auction(item: &mut Item) {
+    let is_ended = item
+        .dutch_auction
+        .as_ref()
+        .map_or(false, |auction| auction.ended_at < exec::block_timestamp());
+    if is_ended && item.tx.is_none() {
+        item.dutch_auction = None;
+    }
+}
+
+/// Calculates the Dutch auction price at the `timestamp`.
+pub fn dutch_auction_price(auction: &DutchAuction, timestamp: u64) -> Price {
+    let duration = auction.ended_at - auction.started_at;
+    let elapsed = timestamp.saturating_sub(auction.started_at).min(duration);
+
+    let (passed, total) = match auction.decay {
+        PriceDecay::Linear => (elapsed, duration),
+        PriceDecay::Stepwise { step } => (elapsed / step, duration / step),
+    };
+
+    let discount = U256::from(auction.start_price - auction.floor_price) * U256::from(passed)
+        / U256::from(total);
+
+    auction.start_price - discount.as_u128()
+}
@@ -0,0 +1,165 @@
+use crate::{
+    contract::MarketHandler,
+    nft_messages::{payouts, Payout},
+};
+use gstd::{exec, prelude::*, ActorId};
+use market_io::*;
+
+/// The treasury fees are set in basis points, 10 000 basis points are 100%.
+pub const BASIS_POINTS: u16 = 10_000;
+const MIN_TREASURY_FEE: u16 = 0;
+const MAX_TREASURT_FEE: u16 = 500;
+
+pub trait FeeHandler {
+    fn update_treasury_fee(&mut self, fee: u16, delay: u64) -> Result<MarketEvent, MarketErr>;
+
+    fn update_treasury(&mut self, treasury_id: &ActorId) -> Result<MarketEvent, MarketErr>;
+
+    /// Applies the scheduled treasury and collection fees whose timelock has passed.
+    fn apply_scheduled_fees(&mut self);
+
+    /// Sets the treasury fee of the NFT contract that overrides the marketplace fee
+    /// or removes the override if `fee` is `None`.
+    /// The fee is scheduled the same way as the marketplace fee.
+    fn set_collection_fee(
+        &mut self,
+        nft_contract_id: &ContractId,
+        fee: Option<u16>,
+        delay: u64,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    /// Returns the treasury account and the treasury fee in basis points
+    /// charged for the sales of the NFT contract.
+    fn treasury(&self, nft_contract_id: &ContractId) -> (ActorId, u16);
+}
+
+impl FeeHandler for Market {
+    fn update_treasury_fee(&mut self, fee: u16, delay: u64) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::FeeManager)?;
+        if !is_valid_treasury_fee(fee) {
+            return Err(MarketErr::WrongTreasuryFee);
+        }
+
+        if delay == 0 {
+            self.treasury_fee = fee;
+            self.scheduled_treasury_fee = None;
+            return Ok(MarketEvent::TreasuryFeeUpdated(fee));
+        }
+
+        let effective_at = exec::block_timestamp().saturating_add(delay);
+        self.scheduled_treasury_fee = Some(ScheduledTreasuryFee { fee, effective_at });
+        Ok(MarketEvent::TreasuryFeeScheduled { fee, effective_at })
+    }
+
+    fn update_treasury(&mut self, treasury_id: &ActorId) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::FeeManager)?;
+        self.treasury_id = *treasury_id;
+        Ok(MarketEvent::TreasuryUpdated(*treasury_id))
+    }
+
+    fn apply_scheduled_fees(&mut self) {
+        let now = exec::block_timestamp();
+        if let Some(ScheduledTreasuryFee { fee, effective_at }) = self.scheduled_treasury_fee {
+            if effective_at <= now {
+                self.treasury_fee = fee;
+                self.scheduled_treasury_fee = None;
+            }
+        }
+
+        let effective: Vec<(ContractId, Option<u16>)> = self
+            .scheduled_collection_fees
+            .iter()
+            .filter(|(_, scheduled)| scheduled.effective_at <= now)
+            .map(|(nft_contract_id, scheduled)| (*nft_contract_id, scheduled.fee))
+            .collect();
+        for (nft_contract_id, fee) in effective {
+            self.scheduled_collection_fees.remove(&nft_contract_id);
+            set_collection_fee(self, &nft_contract_id, fee);
+        }
+    }
+
+    fn set_collection_fee(
+        &mut self,
+        nft_contract_id: &ContractId,
+        fee: Option<u16>,
+        delay: u64,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_role(Role::FeeManager)?;
+        if !self.approved_nft_contracts.contains(nft_contract_id) {
+            return Err(MarketErr::ContractNotApproved);
+        }
+
+        if fee.map_or(false, |fee| !is_valid_treasury_fee(fee)) {
+            return Err(MarketErr::WrongTreasuryFee);
+        }
+
+        if delay == 0 {
+            set_collection_fee(self, nft_contract_id, fee);
+            self.scheduled_collection_fees.remove(nft_contract_id);
+            return Ok(MarketEvent::CollectionFeeUpdated {
+                nft_contract_id: *nft_contract_id,
+                fee,
+            });
+        }
+
+        let effective_at = exec::block_timestamp().saturating_add(delay);
+        self.scheduled_collection_fees.insert(
+            *nft_contract_id,
+            ScheduledCollectionFee { fee, effective_at },
+        );
+        Ok(MarketEvent::CollectionFeeScheduled {
+            nft_contract_id: *nft_contract_id,
+            fee,
+            effective_at,
+        })
+    }
+
+    fn treasury(&self, nft_contract_id: &ContractId) -> (ActorId, u16) {
+        let fee = self
+            .collection_fees
+            .get(nft_contract_id)
+            .copied()
+            .unwrap_or(self.treasury_fee);
+        (self.treasury_id, fee)
+    }
+}
+
+/// Overrides the marketplace fee for the NFT contract or removes the override if `fee` is `None`.
+fn set_collection_fee(market: &mut Market, nft_contract_id: &ContractId, fee: Option<u16>) {
+    match fee {
+        Some(fee) => market.collection_fees.insert(*nft_contract_id, fee),
+        None => market.collection_fees.remove(nft_contract_id),
+    };
+}
+
+// In case when `MIN_TREASURY_FEE` is zero, operator `>` is not required,
+// because this is minimum value. But if `MIN_TREASURY_FEE` could be changed later,
+// usage of `!=` operator can lead to unwanted errors or exploits
+#[allow(clippy::absurd_extreme_comparisons)]
+pub fn is_valid_treasury_fee(fee: u16) -> bool {
+    fee > MIN_TREASURY_FEE && fee <= MAX_TREASURT_FEE
+}
+
+/// Calculates the share of the amount given in basis points.
+pub fn basis_points_share(amount: Price, basis_points: u16) -> Price {
+    amount.saturating_mul(basis_points as u128) / BASIS_POINTS as u128
+}
+
+/// Calculates the treasury fee of the price for the fee in basis points.
+pub fn treasury_fee(price: Price, fee: u16) -> Price {
+    basis_points_share(price, fee)
+}
+
+/// Returns the payouts of the NFT sale: the treasury gets the fee
+/// and the rest is split between the royalty accounts and the owner.
+pub async fn sale_payouts(
+    nft_contract_id: &ContractId,
+    owner: &ActorId,
+    price: Price,
+    (treasury_id, fee): (ActorId, u16),
+) -> Payout {
+    let treasury_fee = treasury_fee(price, fee);
+    let mut payouts = payouts(nft_contract_id, owner, price - treasury_fee).await;
+    payouts.insert(treasury_id, treasury_fee);
+    payouts
+}
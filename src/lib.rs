@@ -1,11 +1,20 @@
 #![no_std]
 
 mod auction;
+mod bundles;
+mod collection_offers;
 pub mod contract;
+mod dutch_auction;
+mod fees;
 mod nft_messages;
 mod offers;
 mod payment;
 mod sale;
+mod sealed_auction;
+mod swaps;
+mod trade;
+
+pub use fees::BASIS_POINTS;
 
 // See `Cargo.toml` for the description of the "binary-vendor" feature.
 #[cfg(feature = "binary-vendor")]
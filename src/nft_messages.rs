@@ -1,5 +1,7 @@
 use gstd::{msg, prelude::*, ActorId};
-use market_io::{ContractId, TokenId, TransactionId};
+use market_io::{
+    Attributes, ContractId, NFTAttributesAction, TokenAttributes, TokenId, TransactionId,
+};
 use nft_io::*;
 use primitive_types::U256;
 
@@ -59,3 +61,54 @@ pub async fn get_owner(nft_contract_id: &ContractId, token_id: TokenId) -> Actor
         _ => panic!("Wrong received message"),
     }
 }
+
+/// Requests the attributes of the NFT.
+///
+/// Returns `None` if the NFT contract doesn't answer [`NFTAttributesAction`]
+/// or replies with the attributes of another token.
+pub async fn get_attributes(nft_contract_id: &ContractId, token_id: TokenId) -> Option<Attributes> {
+    let reply = msg::send_for_reply_as::<_, TokenAttributes>(
+        *nft_contract_id,
+        NFTAttributesAction::TokenAttributes { token_id },
+        0,
+        0,
+    )
+    .expect("Error in sending a message `NFTAttributesAction::TokenAttributes`")
+    .await;
+
+    match reply {
+        Ok(reply) if reply.token_id == token_id => Some(reply.attributes),
+        _ => None,
+    }
+}
+
+/// Requests the owners of several NFTs.
+///
+/// All messages are sent before awaiting the replies,
+/// so the NFT contracts process them in parallel.
+/// If the owner can't be received, the zero address is returned for the NFT.
+pub async fn get_owners(tokens: &[(ContractId, TokenId)]) -> Vec<ActorId> {
+    let replies: Vec<_> = tokens
+        .iter()
+        .map(|(nft_contract_id, token_id)| {
+            msg::send_for_reply_as::<_, NFTEvent>(
+                *nft_contract_id,
+                NFTAction::Owner {
+                    token_id: *token_id,
+                },
+                0,
+                0,
+            )
+            .expect("Error in sending a message `NFTAction::Owner`")
+        })
+        .collect();
+
+    let mut owners = Vec::with_capacity(replies.len());
+    for reply in replies {
+        match reply.await {
+            Ok(NFTEvent::Owner { owner, token_id: _ }) => owners.push(owner),
+            _ => owners.push(ActorId::zero()),
+        }
+    }
+    owners
+}
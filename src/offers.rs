@@ -1,11 +1,13 @@
 use crate::{
-    contract::{BASE_PERCENT, MINIMUM_VALUE},
+    contract::{change_owner, refund_value, MarketHandler, MINIMUM_VALUE},
+    fees::{sale_payouts, FeeHandler},
     nft_messages::*,
     payment::*,
 };
 use gstd::{exec, msg, prelude::*, ActorId};
 use market_io::{
-    ContractId, Item, Market, MarketErr, MarketEvent, MarketTx, Price, TokenId, TransactionId,
+    ContractId, CounterOffer, Item, Market, MarketErr, MarketEvent, MarketTx, Offer, OfferId,
+    Price, TokenId, TransactionId,
 };
 
 #[async_trait::async_trait]
@@ -16,6 +18,7 @@ pub trait OffersHandler {
         ft_contract_id: Option<ContractId>,
         token_id: TokenId,
         price: Price,
+        expires_at: Option<u64>,
     ) -> Result<MarketEvent, MarketErr>;
 
     async fn accept_offer(
@@ -24,6 +27,7 @@ pub trait OffersHandler {
         token_id: TokenId,
         ft_contract_id: Option<ContractId>,
         price: Price,
+        buyer: &ActorId,
     ) -> Result<MarketEvent, MarketErr>;
 
     async fn withdraw(
@@ -33,6 +37,39 @@ pub trait OffersHandler {
         ft_contract_id: Option<ContractId>,
         price: Price,
     ) -> Result<MarketEvent, MarketErr>;
+
+    async fn cleanup_expired_offers(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn counter_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        buyer: &ActorId,
+        counter_price: Price,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn accept_counter_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    fn decline_counter_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr>;
 }
 
 #[async_trait::async_trait]
@@ -43,8 +80,13 @@ impl OffersHandler for Market {
         ft_contract_id: Option<ContractId>,
         token_id: TokenId,
         price: Price,
+        expires_at: Option<u64>,
     ) -> Result<MarketEvent, MarketErr> {
         let contract_and_token_id = (*nft_contract_id, token_id);
+        if let Err(error) = self.check_item_trading_allowed(&contract_and_token_id) {
+            refund_value();
+            return Err(error);
+        }
 
         if let Some(ft_contract_id) = &ft_contract_id {
             let is_ft_approved = self.approved_ft_contracts.contains(ft_contract_id);
@@ -64,22 +106,36 @@ impl OffersHandler for Market {
             return Err(MarketErr::WrongPrice);
         }
 
+        if expires_at.map_or(false, |expires_at| expires_at <= exec::block_timestamp()) {
+            return Err(MarketErr::OfferExpired);
+        }
+
         let item = self
             .items
             .get_mut(&contract_and_token_id)
             .ok_or(MarketErr::ItemDoesNotExists)?;
-        if item.auction.is_some() {
+        if item.auction.is_some() || item.sealed_auction.is_some() {
             return Err(MarketErr::AuctionIsAlreadyExists);
         }
 
-        if item.offers.contains_key(&(ft_contract_id, price)) {
+        if item
+            .offers
+            .contains_key(&(ft_contract_id, price, msg::source()))
+        {
             return Err(MarketErr::OfferAlreadyExists);
         };
 
         let ft_id = if let Some(ft_id) = ft_contract_id {
             ft_id
         } else {
-            item.offers.insert((None, price), msg::source());
+            item.offers.insert(
+                (None, price, msg::source()),
+                Offer {
+                    expires_at,
+                    counter_offer: None,
+                    attributes: Vec::new(),
+                },
+            );
             return Ok(MarketEvent::OfferAdded {
                 nft_contract_id: *nft_contract_id,
                 ft_contract_id,
@@ -94,11 +150,21 @@ impl OffersHandler for Market {
                     ft_id,
                     price,
                     account,
+                    expires_at,
+                    ..
                 } => {
                     let new_price = price;
                     let new_ft_id = ft_id;
-                    let result =
-                        add_offer_tx(tx_id, item, nft_contract_id, &ft_id, token_id, price).await;
+                    let result = add_offer_tx(
+                        tx_id,
+                        item,
+                        nft_contract_id,
+                        &ft_id,
+                        token_id,
+                        price,
+                        expires_at,
+                    )
+                    .await;
                     if account == msg::source() && new_price == price && new_ft_id == ft_id {
                         return result;
                     }
@@ -117,10 +183,21 @@ impl OffersHandler for Market {
                 ft_id,
                 price,
                 account: msg::source(),
+                expires_at,
+                attributes: Vec::new(),
             },
         ));
 
-        add_offer_tx(tx_id, item, nft_contract_id, &ft_id, token_id, price).await
+        add_offer_tx(
+            tx_id,
+            item,
+            nft_contract_id,
+            &ft_id,
+            token_id,
+            price,
+            expires_at,
+        )
+        .await
     }
 
     async fn accept_offer(
@@ -129,18 +206,30 @@ impl OffersHandler for Market {
         token_id: TokenId,
         ft_contract_id: Option<ContractId>,
         price: Price,
+        buyer: &ActorId,
     ) -> Result<MarketEvent, MarketErr> {
         let contract_and_token_id = (*nft_contract_id, token_id);
+        self.check_item_trading_allowed(&contract_and_token_id)?;
+        let treasury = self.treasury(nft_contract_id);
 
         let item = self
             .items
             .get_mut(&contract_and_token_id)
             .ok_or(MarketErr::ItemDoesNotExists)?;
 
-        if item.auction.is_some() {
+        if item.auction.is_some() || item.sealed_auction.is_some() {
             return Err(MarketErr::AuctionIsOpened);
         }
 
+        // the NFT could be transferred outside the marketplace after listing
+        if item.tx.is_none() {
+            let owner = get_owner(nft_contract_id, token_id).await;
+            if owner != item.owner {
+                change_owner(item, owner);
+                return Err(MarketErr::ItemOwnerChanged);
+            }
+        }
+
         if item.owner != msg::source() {
             return Err(MarketErr::OfferShouldAcceptedByOwner);
         }
@@ -149,33 +238,44 @@ impl OffersHandler for Market {
             item.price.is_none(),
             "Remove the item from the sale when accepting the offer"
         );
-        let offers = item.offers.clone();
-
-        let account = offers
-            .get(&(ft_contract_id, price))
+        let offer_id = (ft_contract_id, price, *buyer);
+        let offer = item
+            .offers
+            .get(&offer_id)
             .ok_or(MarketErr::OfferIsNotExists)?;
+        if is_expired(offer) {
+            return Err(MarketErr::OfferExpired);
+        }
 
-        // calculate fee for treasury
-        let treasury_fee = price * (self.treasury_fee * BASE_PERCENT) as u128 / 10_000u128;
-
-        // payouts for NFT sale (includes royalty accounts and seller)
-        let mut payouts = payouts(nft_contract_id, &item.owner, price - treasury_fee).await;
-        payouts.insert(self.treasury_id, treasury_fee);
+        // the rerun pays the treasury of the transaction start
+        let treasury = match item.tx {
+            Some((_, MarketTx::AcceptOffer { treasury, .. })) => treasury,
+            _ => treasury,
+        };
+        // payouts for NFT sale (includes treasury, royalty accounts and seller)
+        let payouts = sale_payouts(nft_contract_id, &item.owner, price, treasury).await;
 
         if let Some((tx_id, tx)) = item.tx.clone() {
             match tx {
-                MarketTx::AcceptOffer => {
-                    return accept_offer_tx(
+                MarketTx::AcceptOffer {
+                    offer_id: pending_offer_id,
+                    ..
+                } if pending_offer_id == offer_id => {
+                    let result = accept_offer_tx(
                         tx_id,
                         item,
                         nft_contract_id,
                         ft_contract_id,
-                        account,
+                        buyer,
                         token_id,
                         price,
                         &payouts,
                     )
                     .await;
+                    if result.is_ok() {
+                        item.offers.remove(&offer_id);
+                    }
+                    return result;
                 }
                 _ => {
                     return Err(MarketErr::WrongTransaction);
@@ -185,19 +285,23 @@ impl OffersHandler for Market {
 
         let tx_id = self.tx_id;
         self.tx_id = self.tx_id.wrapping_add(1);
-        item.tx = Some((tx_id, MarketTx::AcceptOffer));
+        item.tx = Some((tx_id, MarketTx::AcceptOffer { offer_id, treasury }));
 
-        accept_offer_tx(
+        let result = accept_offer_tx(
             tx_id,
             item,
             nft_contract_id,
             ft_contract_id,
-            account,
+            buyer,
             token_id,
             price,
             &payouts,
         )
-        .await
+        .await;
+        if result.is_ok() {
+            item.offers.remove(&offer_id);
+        }
+        result
     }
 
     async fn withdraw(
@@ -214,20 +318,16 @@ impl OffersHandler for Market {
             .get_mut(&contract_and_token_id)
             .ok_or(MarketErr::ItemDoesNotExists)?;
 
-        let account = if let Some(account) = item.offers.get(&(ft_contract_id, price)) {
-            *account
-        } else {
+        let account = msg::source();
+        if !item.offers.contains_key(&(ft_contract_id, price, account)) {
             return Err(MarketErr::OfferIsNotExists);
-        };
-
-        if account != msg::source() {
-            return Err(MarketErr::InvalidCaller);
         }
 
         let ft_id = if let Some(ft_id) = ft_contract_id {
             ft_id
         } else {
             msg::send(account, MarketEvent::TransferValue, price).expect("Error in sending value");
+            item.offers.remove(&(None, price, account));
             return Ok(MarketEvent::Withdraw {
                 nft_contract_id: *nft_contract_id,
                 token_id,
@@ -285,6 +385,355 @@ impl OffersHandler for Market {
         )
         .await
     }
+
+    async fn cleanup_expired_offers(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        if item.tx.is_some() {
+            return Err(MarketErr::WrongTransaction);
+        }
+
+        let expired_offers: Vec<_> = item
+            .offers
+            .iter()
+            .filter(|(_, offer)| is_expired(offer))
+            .map(|(&offer_id, _)| offer_id)
+            .collect();
+
+        refund_offers(
+            &mut self.tx_id,
+            item,
+            nft_contract_id,
+            token_id,
+            &expired_offers,
+        )
+        .await?;
+
+        Ok(MarketEvent::ExpiredOffersCleaned {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            count: expired_offers.len() as u32,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn counter_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        buyer: &ActorId,
+        counter_price: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        if item.auction.is_some() || item.sealed_auction.is_some() {
+            return Err(MarketErr::AuctionIsOpened);
+        }
+
+        if item.owner != msg::source() {
+            return Err(MarketErr::OfferShouldAcceptedByOwner);
+        }
+
+        #[allow(clippy::absurd_extreme_comparisons)]
+        if ft_contract_id.is_some() && counter_price <= 0
+            || ft_contract_id.is_none() && counter_price <= MINIMUM_VALUE.into()
+            || counter_price == price
+        {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        // the excess of the native value must be large enough to be returned to the offer creator
+        if ft_contract_id.is_none()
+            && price > counter_price
+            && price - counter_price <= MINIMUM_VALUE.into()
+        {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        let offer = item
+            .offers
+            .get_mut(&(ft_contract_id, price, *buyer))
+            .ok_or(MarketErr::OfferIsNotExists)?;
+        if is_expired(offer) {
+            return Err(MarketErr::OfferExpired);
+        }
+
+        offer.counter_offer = Some(CounterOffer {
+            owner: item.owner,
+            price: counter_price,
+        });
+
+        Ok(MarketEvent::CounterOfferMade {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            buyer: *buyer,
+            price,
+            counter_price,
+        })
+    }
+
+    async fn accept_counter_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+        let offer_id = (ft_contract_id, price, msg::source());
+        if let Err(error) = self.check_item_trading_allowed(&contract_and_token_id) {
+            refund_value();
+            return Err(error);
+        }
+        let treasury = self.treasury(nft_contract_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        if item.auction.is_some() || item.sealed_auction.is_some() {
+            return Err(MarketErr::AuctionIsOpened);
+        }
+
+        if let Some((tx_id, tx)) = item.tx.clone() {
+            match tx {
+                MarketTx::AcceptCounterOffer {
+                    offer_id: pending_offer_id,
+                    counter_price,
+                    treasury,
+                } if pending_offer_id == offer_id => {
+                    // payouts for NFT sale (includes treasury, royalty accounts and seller)
+                    let payouts =
+                        sale_payouts(nft_contract_id, &item.owner, counter_price, treasury).await;
+
+                    return accept_counter_offer_tx(
+                        tx_id,
+                        item,
+                        nft_contract_id,
+                        token_id,
+                        offer_id,
+                        counter_price,
+                        &payouts,
+                    )
+                    .await;
+                }
+                _ => {
+                    return Err(MarketErr::WrongTransaction);
+                }
+            }
+        }
+
+        assert!(
+            item.price.is_none(),
+            "Remove the item from the sale when accepting the offer"
+        );
+
+        let offer = item
+            .offers
+            .get(&offer_id)
+            .ok_or(MarketErr::OfferIsNotExists)?;
+        let counter_price = match &offer.counter_offer {
+            Some(counter_offer) if counter_offer.owner == item.owner => counter_offer.price,
+            _ => return Err(MarketErr::CounterOfferIsNotExists),
+        };
+        if is_expired(offer) {
+            return Err(MarketErr::OfferExpired);
+        }
+
+        if item
+            .offers
+            .contains_key(&(ft_contract_id, counter_price, offer_id.2))
+        {
+            return Err(MarketErr::OfferAlreadyExists);
+        }
+
+        if ft_contract_id.is_none() && msg::value() != counter_price.saturating_sub(price) {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        // the NFT could be transferred outside the marketplace after the counter-offer
+        let owner = get_owner(nft_contract_id, token_id).await;
+        if owner != item.owner {
+            change_owner(item, owner);
+            refund_value();
+            return Err(MarketErr::ItemOwnerChanged);
+        }
+
+        // payouts for NFT sale (includes treasury, royalty accounts and seller)
+        let payouts = sale_payouts(nft_contract_id, &item.owner, counter_price, treasury).await;
+
+        // another transaction could be started or the offers changed during the owner and payouts queries
+        let counter_offer = item
+            .offers
+            .get(&offer_id)
+            .map(|offer| offer.counter_offer.as_ref());
+        let result = if item.tx.is_some() {
+            Err(MarketErr::WrongTransaction)
+        } else if item.auction.is_some() || item.sealed_auction.is_some() {
+            Err(MarketErr::AuctionIsOpened)
+        } else if counter_offer.is_none() {
+            Err(MarketErr::OfferIsNotExists)
+        } else if !counter_offer.flatten().map_or(false, |counter_offer| {
+            counter_offer.owner == item.owner && counter_offer.price == counter_price
+        }) {
+            Err(MarketErr::CounterOfferIsNotExists)
+        } else if item
+            .offers
+            .contains_key(&(ft_contract_id, counter_price, offer_id.2))
+        {
+            Err(MarketErr::OfferAlreadyExists)
+        } else {
+            Ok(())
+        };
+        if let Err(error) = result {
+            refund_value();
+            return Err(error);
+        }
+
+        let tx_id = self.tx_id;
+        self.tx_id = self.tx_id.wrapping_add(payouts.len() as u64 + 2);
+        item.tx = Some((
+            tx_id,
+            MarketTx::AcceptCounterOffer {
+                offer_id,
+                counter_price,
+                treasury,
+            },
+        ));
+
+        accept_counter_offer_tx(
+            tx_id,
+            item,
+            nft_contract_id,
+            token_id,
+            offer_id,
+            counter_price,
+            &payouts,
+        )
+        .await
+    }
+
+    fn decline_counter_offer(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+        let buyer = msg::source();
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        let offer = item
+            .offers
+            .get_mut(&(ft_contract_id, price, buyer))
+            .ok_or(MarketErr::OfferIsNotExists)?;
+        if offer.counter_offer.take().is_none() {
+            return Err(MarketErr::CounterOfferIsNotExists);
+        }
+
+        Ok(MarketEvent::CounterOfferDeclined {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            buyer,
+            price,
+        })
+    }
+}
+
+pub fn is_expired(offer: &Offer) -> bool {
+    offer
+        .expires_at
+        .map_or(false, |expires_at| expires_at < exec::block_timestamp())
+}
+
+/// Moves the offer to the counter price adjusting the escrow
+/// and sells the NFT to the offer creator for that price.
+async fn accept_counter_offer_tx(
+    tx_id: TransactionId,
+    item: &mut Item,
+    nft_contract_id: &ContractId,
+    token_id: TokenId,
+    offer_id: OfferId,
+    counter_price: Price,
+    payouts: &Payout,
+) -> Result<MarketEvent, MarketErr> {
+    let (ft_contract_id, price, buyer) = offer_id;
+
+    // the offer is already moved if the transaction is rerun after that step
+    if item.offers.contains_key(&offer_id) {
+        if let Some(ft_id) = ft_contract_id {
+            let result = if counter_price > price {
+                transfer_tokens(
+                    tx_id,
+                    &ft_id,
+                    &buyer,
+                    &exec::program_id(),
+                    counter_price - price,
+                )
+                .await
+            } else {
+                transfer_tokens(
+                    tx_id,
+                    &ft_id,
+                    &exec::program_id(),
+                    &buyer,
+                    price - counter_price,
+                )
+                .await
+            };
+            if result.is_err() {
+                item.tx = None;
+                return Err(MarketErr::TokenTransferFailed);
+            }
+        } else if price > counter_price && price - counter_price > MINIMUM_VALUE.into() {
+            // the missing value is attached to the message, so only the excess is returned
+            msg::send(buyer, MarketEvent::TransferValue, price - counter_price)
+                .expect("Error in sending value");
+        }
+
+        let mut offer = item.offers.remove(&offer_id).expect("Can't be None");
+        offer.counter_offer = None;
+        item.offers
+            .insert((ft_contract_id, counter_price, buyer), offer);
+    }
+
+    let result = accept_offer_tx(
+        tx_id.wrapping_add(1),
+        item,
+        nft_contract_id,
+        ft_contract_id,
+        &buyer,
+        token_id,
+        counter_price,
+        payouts,
+    )
+    .await;
+    if result.is_ok() {
+        item.offers.remove(&(ft_contract_id, counter_price, buyer));
+    }
+    result
 }
 
 async fn add_offer_tx(
@@ -294,6 +743,7 @@ async fn add_offer_tx(
     ft_contract_id: &ContractId,
     token_id: TokenId,
     price: Price,
+    expires_at: Option<u64>,
 ) -> Result<MarketEvent, MarketErr> {
     let ft_id = Some(*ft_contract_id);
     if transfer_tokens(
@@ -311,7 +761,14 @@ async fn add_offer_tx(
     }
 
     item.tx = None;
-    item.offers.insert((ft_id, price), msg::source());
+    item.offers.insert(
+        (ft_id, price, msg::source()),
+        Offer {
+            expires_at,
+            counter_offer: None,
+            attributes: Vec::new(),
+        },
+    );
 
     Ok(MarketEvent::OfferAdded {
         nft_contract_id: *nft_contract_id,
@@ -322,7 +779,7 @@ async fn add_offer_tx(
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn accept_offer_tx(
+pub async fn accept_offer_tx(
     mut tx_id: TransactionId,
     item: &mut Item,
     nft_contract_id: &ContractId,
@@ -378,8 +835,9 @@ async fn accept_offer_tx(
 
     item.owner = *new_owner;
     item.price = None;
+    item.expires_at = None;
+    item.dutch_auction = None;
     item.tx = None;
-    item.offers.remove(&(ft_contract_id, price));
 
     Ok(MarketEvent::OfferAccepted {
         nft_contract_id: *nft_contract_id,
@@ -417,10 +875,10 @@ pub async fn accept_offer_tx_with_value(
 
     item.owner = *new_owner;
     item.price = None;
+    item.expires_at = None;
+    item.dutch_auction = None;
     item.tx = None;
 
-    item.offers.remove(&(None, price));
-
     Ok(MarketEvent::OfferAccepted {
         nft_contract_id: *nft_contract_id,
         token_id,
@@ -429,6 +887,46 @@ pub async fn accept_offer_tx_with_value(
     })
 }
 
+/// Returns the tokens of the offers to their creators and removes the offers.
+pub async fn refund_offers(
+    tx_id: &mut TransactionId,
+    item: &mut Item,
+    nft_contract_id: &ContractId,
+    token_id: TokenId,
+    offer_ids: &[OfferId],
+) -> Result<(), MarketErr> {
+    for (ft_contract_id, price, account) in offer_ids.iter() {
+        if let Some(ft_id) = ft_contract_id {
+            let withdraw_tx_id = *tx_id;
+            *tx_id = tx_id.wrapping_add(1);
+            item.tx = Some((
+                withdraw_tx_id,
+                MarketTx::Withdraw {
+                    ft_id: *ft_id,
+                    price: *price,
+                    account: *account,
+                },
+            ));
+            withdraw_tx(
+                withdraw_tx_id,
+                item,
+                nft_contract_id,
+                ft_id,
+                token_id,
+                account,
+                *price,
+            )
+            .await?;
+        } else {
+            msg::send(*account, MarketEvent::TransferValue, *price)
+                .expect("Error in sending value");
+            item.offers.remove(&(None, *price, *account));
+        }
+    }
+
+    Ok(())
+}
+
 async fn withdraw_tx(
     tx_id: TransactionId,
     item: &mut Item,
@@ -447,7 +945,8 @@ async fn withdraw_tx(
     }
 
     item.tx = None;
-    item.offers.remove(&(Some(*ft_contract_id), price));
+    item.offers
+        .remove(&(Some(*ft_contract_id), price, *account));
 
     Ok(MarketEvent::Withdraw {
         nft_contract_id: *nft_contract_id,
@@ -1,11 +1,13 @@
 use crate::{
-    contract::{BASE_PERCENT, MINIMUM_VALUE},
+    contract::{change_owner, refund_value, MarketHandler, MINIMUM_VALUE},
+    dutch_auction::dutch_auction_price,
+    fees::{sale_payouts, FeeHandler},
     nft_messages::*,
     payment::*,
 };
 use gstd::{exec, msg, prelude::*, ActorId};
 use market_io::{
-    ContractId, Item, Market, MarketErr, MarketEvent, MarketTx, TokenId, TransactionId,
+    ContractId, Item, Market, MarketErr, MarketEvent, MarketTx, Price, TokenId, TransactionId,
 };
 
 #[async_trait::async_trait]
@@ -15,6 +17,13 @@ pub trait SaleHandler {
         nft_contract_id: &ContractId,
         token_id: TokenId,
     ) -> Result<MarketEvent, MarketErr>;
+
+    async fn buy_items(
+        &mut self,
+        items: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        max_total_price: Price,
+    ) -> Result<MarketEvent, MarketErr>;
 }
 
 #[async_trait::async_trait]
@@ -24,69 +33,221 @@ impl SaleHandler for Market {
         nft_contract_id: &ContractId,
         token_id: TokenId,
     ) -> Result<MarketEvent, MarketErr> {
-        let contract_and_token_id = (*nft_contract_id, token_id);
+        let result = buy_item_for_value(self, nft_contract_id, token_id, msg::value()).await;
 
-        if let Some(item) = self.items.get_mut(&contract_and_token_id) {
-            if item.auction.is_some() {
-                return Err(MarketErr::ItemOnAuction);
-            }
-            assert!(item.auction.is_none(), "There is an opened auction");
+        // the buyer doesn't pay for the item that the seller doesn't own or that can't be traded
+        if matches!(
+            result,
+            Err(MarketErr::ItemOwnerChanged | MarketErr::ContractNotApproved)
+        ) {
+            refund_value();
+        }
+
+        result
+    }
 
-            let Some(price) = item.price else {
-                return Err(MarketErr::ItemIsNotOnSale);
+    async fn buy_items(
+        &mut self,
+        items: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        max_total_price: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        let value = if ft_contract_id.is_some() {
+            0
+        } else {
+            max_total_price
+        };
+        if msg::value() != value {
+            refund_value();
+            return Err(MarketErr::WrongPrice);
+        }
+
+        let mut total_price: Price = 0;
+        let mut results = Vec::with_capacity(items.len());
+        for (nft_contract_id, token_id) in items {
+            let price = match self.items.get(&(nft_contract_id, token_id)) {
+                Some(item) if item.ft_contract_id != ft_contract_id => {
+                    Err(MarketErr::WrongCurrency)
+                }
+                Some(item) => item_price(item),
+                None => Err(MarketErr::ItemDoesNotExists),
             };
 
-            // calculate fee for treasury
-            let treasury_fee = price * (self.treasury_fee * BASE_PERCENT) as u128 / 10_000u128;
-
-            // payouts for NFT sale (includes royalty accounts and seller)
-            let mut payouts = payouts(nft_contract_id, &item.owner, price - treasury_fee).await;
-            payouts.insert(self.treasury_id, treasury_fee);
-
-            if let Some((tx_id, tx)) = item.tx.clone() {
-                match tx {
-                    MarketTx::Sale { buyer } => {
-                        if buyer != msg::source() {
-                            return Err(MarketErr::WrongTransaction);
-                        }
-                        return buy_item_tx(
-                            tx_id,
-                            item,
-                            nft_contract_id,
-                            &buyer,
-                            token_id,
-                            &payouts,
-                        )
-                        .await;
-                    }
-                    _ => {
+            // the item is skipped if it can't be bought
+            let (result, spent) = match price.and_then(|price| {
+                total_price
+                    .checked_add(price)
+                    .filter(|total_price| *total_price <= max_total_price)
+                    .map(|_| price)
+                    .ok_or(MarketErr::SpendLimitExceeded)
+            }) {
+                Ok(price) => {
+                    // the remaining value that is too small to be returned is paid with the item
+                    let remaining = max_total_price - total_price - price;
+                    let spent = if ft_contract_id.is_none() && remaining <= MINIMUM_VALUE.into() {
+                        price + remaining
+                    } else {
+                        price
+                    };
+                    let result = buy_item_for_value(self, &nft_contract_id, token_id, spent)
+                        .await
+                        .map(|_| price);
+                    (result, spent)
+                }
+                Err(error) => (Err(error), 0),
+            };
+            // the failed transaction is completed by the rerun,
+            // so its price is counted as the buyer's tokens may be already moved
+            if matches!(result, Ok(_) | Err(MarketErr::RerunTransaction)) {
+                total_price += spent;
+            }
+            results.push((nft_contract_id, token_id, result));
+        }
+
+        // return the value that isn't spent
+        let change = max_total_price - total_price;
+        if ft_contract_id.is_none() && change > MINIMUM_VALUE.into() {
+            msg::send(msg::source(), MarketEvent::TransferValue, change)
+                .expect("Error in sending value");
+        }
+
+        Ok(MarketEvent::ItemsBought(results))
+    }
+}
+
+/// Returns the price for which the item can be bought at the current block.
+fn item_price(item: &Item) -> Result<Price, MarketErr> {
+    if item.auction.is_some() || item.sealed_auction.is_some() {
+        return Err(MarketErr::ItemOnAuction);
+    }
+
+    if let Some((_, MarketTx::Sale { price, .. })) = &item.tx {
+        Ok(*price)
+    } else if let Some(dutch_auction) = &item.dutch_auction {
+        if dutch_auction.ended_at < exec::block_timestamp() {
+            return Err(MarketErr::AuctionIsAlreadyEnded);
+        }
+        Ok(dutch_auction_price(dutch_auction, exec::block_timestamp()))
+    } else if let Some(price) = item.price {
+        if item
+            .expires_at
+            .map_or(false, |expires_at| expires_at < exec::block_timestamp())
+        {
+            return Err(MarketErr::ListingExpired);
+        }
+        Ok(price)
+    } else {
+        Err(MarketErr::ItemIsNotOnSale)
+    }
+}
+
+/// Sells the NFT, the native value is paid from the `value`.
+async fn buy_item_for_value(
+    market: &mut Market,
+    nft_contract_id: &ContractId,
+    token_id: TokenId,
+    value: u128,
+) -> Result<MarketEvent, MarketErr> {
+    let contract_and_token_id = (*nft_contract_id, token_id);
+    market.check_item_trading_allowed(&contract_and_token_id)?;
+    let treasury = market.treasury(nft_contract_id);
+
+    if let Some(item) = market.items.get_mut(&contract_and_token_id) {
+        let price = item_price(item)?;
+        assert!(item.auction.is_none(), "There is an opened auction");
+
+        // the NFT could be transferred outside the marketplace after listing
+        if item.tx.is_none() {
+            let owner = get_owner(nft_contract_id, token_id).await;
+            if owner != item.owner {
+                change_owner(item, owner);
+                return Err(MarketErr::ItemOwnerChanged);
+            }
+        }
+
+        // the rerun pays the treasury of the transaction start
+        let treasury = match item.tx {
+            Some((_, MarketTx::Sale { treasury, .. })) => treasury,
+            _ => treasury,
+        };
+        // payouts for NFT sale (includes treasury, royalty accounts and seller)
+        let payouts = sale_payouts(nft_contract_id, &item.owner, price, treasury).await;
+
+        if let Some((tx_id, tx)) = item.tx.clone() {
+            match tx {
+                MarketTx::Sale { buyer, price, .. } => {
+                    if buyer != msg::source() {
                         return Err(MarketErr::WrongTransaction);
                     }
+                    return buy_item_tx(
+                        tx_id,
+                        item,
+                        nft_contract_id,
+                        &buyer,
+                        token_id,
+                        price,
+                        &payouts,
+                        value,
+                    )
+                    .await;
+                }
+                _ => {
+                    return Err(MarketErr::WrongTransaction);
                 }
             }
-            let buyer = msg::source();
-            let tx_id = self.tx_id;
-            item.tx = Some((tx_id, MarketTx::Sale { buyer }));
-            buy_item_tx(tx_id, item, nft_contract_id, &buyer, token_id, &payouts).await
-        } else {
-            Err(MarketErr::ItemDoesNotExists)
         }
+        let buyer = msg::source();
+        let tx_id = market.tx_id;
+        market.tx_id = market.tx_id.wrapping_add(payouts.len() as u64 + 1);
+        item.tx = Some((
+            tx_id,
+            MarketTx::Sale {
+                buyer,
+                price,
+                treasury,
+            },
+        ));
+        buy_item_tx(
+            tx_id,
+            item,
+            nft_contract_id,
+            &buyer,
+            token_id,
+            price,
+            &payouts,
+            value,
+        )
+        .await
+    } else {
+        Err(MarketErr::ItemDoesNotExists)
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn buy_item_tx(
     mut tx_id: TransactionId,
     item: &mut Item,
     nft_contract_id: &ContractId,
     new_owner: &ActorId,
     token_id: TokenId,
+    price: Price,
     payouts: &Payout,
+    value: u128,
 ) -> Result<MarketEvent, MarketErr> {
     let ft_id = if let Some(ft_contract_id) = item.ft_contract_id {
         ft_contract_id
     } else {
-        return buy_item_tx_with_value(tx_id, item, nft_contract_id, new_owner, token_id, payouts)
-            .await;
+        return buy_item_tx_with_value(
+            tx_id,
+            item,
+            nft_contract_id,
+            new_owner,
+            token_id,
+            price,
+            payouts,
+            value,
+        )
+        .await;
     };
 
     // transfer NFT to the marketplace account
@@ -98,8 +259,6 @@ async fn buy_item_tx(
         return Err(MarketErr::NFTTransferFailed);
     }
 
-    let price = item.price.expect("Can't be None");
-
     // transfer tokens to the marketplace account
     if transfer_tokens(tx_id, &ft_id, new_owner, &exec::program_id(), price)
         .await
@@ -139,6 +298,8 @@ async fn buy_item_tx(
 
     item.owner = *new_owner;
     item.price = None;
+    item.expires_at = None;
+    item.dutch_auction = None;
     item.tx = None;
 
     Ok(MarketEvent::ItemSold {
@@ -148,16 +309,18 @@ async fn buy_item_tx(
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn buy_item_tx_with_value(
     tx_id: TransactionId,
     item: &mut Item,
     nft_contract_id: &ContractId,
     new_owner: &ActorId,
     token_id: TokenId,
+    price: Price,
     payouts: &Payout,
+    value: u128,
 ) -> Result<MarketEvent, MarketErr> {
-    let price = item.price.expect("Can't be None");
-    if msg::value() < price {
+    if value < price {
         return Err(MarketErr::WrongPrice);
     }
 
@@ -170,6 +333,14 @@ pub async fn buy_item_tx_with_value(
         return Err(MarketErr::NFTTransferFailed);
     }
 
+    // the change (e.g. if the Dutch auction price has dropped) is returned to the buyer,
+    // but if it's too small to be sent, it's paid to the seller
+    let change = value - price;
+    let mut payouts = payouts.clone();
+    if change <= MINIMUM_VALUE.into() {
+        *payouts.entry(item.owner).or_default() += change;
+    }
+
     // send tokens to the seller, royalties and tresuary account
     // since tokens are on the marketplace account, the error can be only due the lack of gas
     for (account, amount) in payouts.iter() {
@@ -178,8 +349,14 @@ pub async fn buy_item_tx_with_value(
         }
     }
 
+    if change > MINIMUM_VALUE.into() {
+        msg::send(*new_owner, MarketEvent::TransferValue, change).expect("Error in sending value");
+    }
+
     item.owner = *new_owner;
     item.price = None;
+    item.expires_at = None;
+    item.dutch_auction = None;
     item.tx = None;
 
     Ok(MarketEvent::ItemSold {
@@ -0,0 +1,556 @@
+use crate::{
+    contract::{refund_value, MarketHandler, MINIMUM_VALUE},
+    dutch_auction::clear_ended_dutch_auction,
+    fees::{sale_payouts, FeeHandler},
+    nft_messages::{nft_transfer, Payout},
+    payment::transfer_tokens,
+};
+use gstd::{exec, msg, prelude::*, ActorId};
+use market_io::*;
+use sp_core_hashing::blake2_256;
+
+const MIN_PHASE_DURATION: u64 = 60_000;
+
+#[async_trait::async_trait]
+pub trait SealedAuctionHandler {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_sealed_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        token_id: TokenId,
+        min_price: Price,
+        pricing: SealedBidPricing,
+        commit_duration: u64,
+        reveal_duration: u64,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn commit_bid(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        commitment: [u8; 32],
+        deposit: Price,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    fn reveal_bid(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        price: Price,
+        salt: [u8; 32],
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn settle_sealed_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr>;
+}
+
+#[async_trait::async_trait]
+impl SealedAuctionHandler for Market {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_sealed_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        ft_contract_id: Option<ContractId>,
+        token_id: TokenId,
+        min_price: Price,
+        pricing: SealedBidPricing,
+        commit_duration: u64,
+        reveal_duration: u64,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_approved_nft_contract(nft_contract_id);
+        self.check_approved_ft_contract(ft_contract_id);
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        assert_eq!(
+            item.owner,
+            msg::source(),
+            "Only owner has a right to add NFT to the marketplace and start the auction"
+        );
+
+        clear_ended_dutch_auction(item);
+        if item.auction.is_some() || item.dutch_auction.is_some() || item.sealed_auction.is_some() {
+            return Err(MarketErr::AuctionIsAlreadyExists);
+        }
+
+        assert!(
+            item.price.is_none(),
+            "Remove the item from the sale before starting the auction"
+        );
+
+        if commit_duration < MIN_PHASE_DURATION || reveal_duration < MIN_PHASE_DURATION {
+            return Err(MarketErr::AuctionBidPeriodOrDurationIsInvalid);
+        }
+
+        #[allow(clippy::absurd_extreme_comparisons)]
+        if min_price <= 0 {
+            return Err(MarketErr::AuctionMinPriceIsZero);
+        }
+
+        let tx_id = match item.tx {
+            Some((tx_id, MarketTx::CreateSealedAuction)) => tx_id,
+            Some(_) => return Err(MarketErr::WrongTransaction),
+            None => {
+                let tx_id = self.tx_id;
+                self.tx_id = self.tx_id.wrapping_add(1);
+                item.tx = Some((tx_id, MarketTx::CreateSealedAuction));
+                tx_id
+            }
+        };
+
+        create_sealed_auction_tx(
+            tx_id,
+            item,
+            nft_contract_id,
+            ft_contract_id,
+            token_id,
+            min_price,
+            pricing,
+            commit_duration,
+            reveal_duration,
+        )
+        .await
+    }
+
+    async fn commit_bid(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        commitment: [u8; 32],
+        deposit: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+        if let Err(error) = self.check_item_trading_allowed(&contract_and_token_id) {
+            refund_value();
+            return Err(error);
+        }
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        let Some(auction) = item.sealed_auction.as_mut() else {
+            return Err(MarketErr::AuctionDoesNotExists);
+        };
+
+        if auction.commit_ended_at < exec::block_timestamp() {
+            return Err(MarketErr::AuctionIsAlreadyEnded);
+        }
+
+        if auction.bids.contains_key(&msg::source()) {
+            return Err(MarketErr::BidAlreadyCommitted);
+        }
+
+        if deposit < auction.min_price {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        let ft_id = if let Some(ft_id) = item.ft_contract_id {
+            ft_id
+        } else {
+            if msg::value() != deposit {
+                return Err(MarketErr::WrongPrice);
+            }
+
+            auction.bids.insert(
+                msg::source(),
+                SealedBid {
+                    commitment,
+                    deposit,
+                    revealed_price: None,
+                },
+            );
+
+            return Ok(MarketEvent::SealedBidCommitted {
+                nft_contract_id: *nft_contract_id,
+                token_id,
+                account: msg::source(),
+            });
+        };
+
+        if let Some((tx_id, tx)) = item.tx.clone() {
+            let MarketTx::SealedBid { account, commitment, deposit } = tx else {
+                return Err(MarketErr::WrongTransaction);
+            };
+
+            let result = commit_bid_tx(
+                tx_id,
+                item,
+                nft_contract_id,
+                &ft_id,
+                token_id,
+                &account,
+                commitment,
+                deposit,
+            )
+            .await;
+            if account == msg::source() {
+                return result;
+            }
+        }
+
+        let tx_id = self.tx_id;
+        self.tx_id = self.tx_id.wrapping_add(1);
+        item.tx = Some((
+            tx_id,
+            MarketTx::SealedBid {
+                account: msg::source(),
+                commitment,
+                deposit,
+            },
+        ));
+
+        commit_bid_tx(
+            tx_id,
+            item,
+            nft_contract_id,
+            &ft_id,
+            token_id,
+            &msg::source(),
+            commitment,
+            deposit,
+        )
+        .await
+    }
+
+    fn reveal_bid(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+        price: Price,
+        salt: [u8; 32],
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        let Some(auction) = item.sealed_auction.as_mut() else {
+            return Err(MarketErr::AuctionDoesNotExists);
+        };
+
+        let now = exec::block_timestamp();
+        if now <= auction.commit_ended_at || now > auction.reveal_ended_at {
+            return Err(MarketErr::RevealPhaseIsNotActive);
+        }
+
+        let account = msg::source();
+        let bid = auction
+            .bids
+            .get_mut(&account)
+            .ok_or(MarketErr::BidIsNotCommitted)?;
+
+        if blake2_256(&(account, price, salt).encode()) != bid.commitment {
+            return Err(MarketErr::WrongCommitment);
+        }
+
+        if price < auction.min_price || price > bid.deposit {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        bid.revealed_price = Some(price);
+
+        Ok(MarketEvent::SealedBidRevealed {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+            account,
+            price,
+        })
+    }
+
+    async fn settle_sealed_auction(
+        &mut self,
+        nft_contract_id: &ContractId,
+        token_id: TokenId,
+    ) -> Result<MarketEvent, MarketErr> {
+        let contract_and_token_id = (*nft_contract_id, token_id);
+        let treasury = self.treasury(nft_contract_id);
+
+        let item = self
+            .items
+            .get_mut(&contract_and_token_id)
+            .ok_or(MarketErr::ItemDoesNotExists)?;
+
+        let Some(auction) = &item.sealed_auction else {
+            return Err(MarketErr::AuctionDoesNotExists);
+        };
+
+        if auction.reveal_ended_at > exec::block_timestamp() {
+            return Err(MarketErr::AuctionIsNotOver);
+        }
+
+        if let Some((tx_id, tx)) = item.tx.clone() {
+            match tx {
+                // the deposit must be accepted to be returned during the settlement
+                MarketTx::SealedBid {
+                    account,
+                    commitment,
+                    deposit,
+                } => {
+                    let ft_id = item.ft_contract_id.expect("Can't be None");
+                    commit_bid_tx(
+                        tx_id,
+                        item,
+                        nft_contract_id,
+                        &ft_id,
+                        token_id,
+                        &account,
+                        commitment,
+                        deposit,
+                    )
+                    .await?;
+                }
+                MarketTx::SettleSealedAuction { .. } => {}
+                _ => {
+                    return Err(MarketErr::WrongTransaction);
+                }
+            }
+        }
+
+        let auction = item.sealed_auction.as_ref().expect("Can't be None");
+        let winner = sealed_auction_winner(auction);
+
+        // the rerun pays the treasury of the transaction start
+        let treasury = match item.tx {
+            Some((_, MarketTx::SettleSealedAuction { treasury })) => treasury,
+            _ => treasury,
+        };
+        let payouts = if let Some((_, price)) = winner {
+            // payouts for NFT sale (includes treasury, royalty accounts and seller)
+            sale_payouts(nft_contract_id, &item.owner, price, treasury).await
+        } else {
+            Payout::new()
+        };
+
+        let tx_id = if let Some((tx_id, MarketTx::SettleSealedAuction { .. })) = item.tx {
+            tx_id
+        } else {
+            let bids_amount = auction.bids.len() as u64;
+            let tx_id = self.tx_id;
+            self.tx_id = self
+                .tx_id
+                .wrapping_add(bids_amount + payouts.len() as u64 + 1);
+            item.tx = Some((tx_id, MarketTx::SettleSealedAuction { treasury }));
+            tx_id
+        };
+
+        settle_sealed_auction_tx(tx_id, item, winner, &payouts, nft_contract_id, token_id).await
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_sealed_auction_tx(
+    tx_id: TransactionId,
+    item: &mut Item,
+    nft_contract_id: &ContractId,
+    ft_contract_id: Option<ContractId>,
+    token_id: TokenId,
+    min_price: Price,
+    pricing: SealedBidPricing,
+    commit_duration: u64,
+    reveal_duration: u64,
+) -> Result<MarketEvent, MarketErr> {
+    if nft_transfer(tx_id, nft_contract_id, &exec::program_id(), token_id)
+        .await
+        .is_err()
+    {
+        item.tx = None;
+        return Err(MarketErr::NFTTransferFailed);
+    }
+
+    let started_at = exec::block_timestamp();
+    let commit_ended_at = started_at + commit_duration;
+    item.ft_contract_id = ft_contract_id;
+    item.sealed_auction = Some(SealedAuction {
+        min_price,
+        pricing,
+        started_at,
+        commit_ended_at,
+        reveal_ended_at: commit_ended_at + reveal_duration,
+        bids: BTreeMap::new(),
+    });
+    item.tx = None;
+
+    Ok(MarketEvent::SealedAuctionCreated {
+        nft_contract_id: *nft_contract_id,
+        token_id,
+        min_price,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn commit_bid_tx(
+    tx_id: TransactionId,
+    item: &mut Item,
+    nft_contract_id: &ContractId,
+    ft_contract_id: &ContractId,
+    token_id: TokenId,
+    account: &ActorId,
+    commitment: [u8; 32],
+    deposit: Price,
+) -> Result<MarketEvent, MarketErr> {
+    if transfer_tokens(tx_id, ft_contract_id, account, &exec::program_id(), deposit)
+        .await
+        .is_err()
+    {
+        item.tx = None;
+        return Err(MarketErr::TokenTransferFailed);
+    }
+
+    item.tx = None;
+    item.sealed_auction
+        .as_mut()
+        .expect("Can't be None")
+        .bids
+        .insert(
+            *account,
+            SealedBid {
+                commitment,
+                deposit,
+                revealed_price: None,
+            },
+        );
+
+    Ok(MarketEvent::SealedBidCommitted {
+        nft_contract_id: *nft_contract_id,
+        token_id,
+        account: *account,
+    })
+}
+
+async fn settle_sealed_auction_tx(
+    mut tx_id: TransactionId,
+    item: &mut Item,
+    winner: Option<(ActorId, Price)>,
+    payouts: &Payout,
+    nft_contract_id: &ContractId,
+    token_id: TokenId,
+) -> Result<MarketEvent, MarketErr> {
+    let auction = item.sealed_auction.as_mut().expect("Can't be None");
+
+    // return deposits to bidders (the winner gets back the rest of the deposit)
+    // since tokens are on the marketplace account, the error can be only due the lack of gas
+    for (account, bid) in auction.bids.iter_mut() {
+        tx_id = tx_id.wrapping_add(1);
+        if bid.deposit == 0 {
+            continue;
+        }
+
+        let amount = match winner {
+            Some((winner, price)) if winner == *account => bid.deposit - price,
+            _ => bid.deposit,
+        };
+
+        if let Some(ft_id) = item.ft_contract_id {
+            if amount > 0
+                && transfer_tokens(tx_id, &ft_id, &exec::program_id(), account, amount)
+                    .await
+                    .is_err()
+            {
+                return Err(MarketErr::RerunTransaction);
+            }
+        } else if amount > MINIMUM_VALUE.into() {
+            msg::send(*account, MarketEvent::TransferValue, amount)
+                .expect("Error in sending value");
+        }
+
+        bid.deposit = 0;
+    }
+
+    let Some((winner, price)) = winner else {
+        // no bids were revealed, so the NFT is returned to the owner
+        if nft_transfer(tx_id, nft_contract_id, &item.owner, token_id)
+            .await
+            .is_err()
+        {
+            return Err(MarketErr::RerunTransaction);
+        }
+
+        item.sealed_auction = None;
+        item.tx = None;
+
+        return Ok(MarketEvent::AuctionCancelled {
+            nft_contract_id: *nft_contract_id,
+            token_id,
+        });
+    };
+
+    // send tokens to the seller, royalties and tresuary account
+    // since tokens are on the marketplace account, the error can be only due the lack of gas
+    if let Some(ft_id) = item.ft_contract_id {
+        for (account, amount) in payouts.iter() {
+            tx_id = tx_id.wrapping_add(1);
+            if transfer_tokens(tx_id, &ft_id, &exec::program_id(), account, *amount)
+                .await
+                .is_err()
+            {
+                return Err(MarketErr::RerunTransaction);
+            };
+        }
+    } else {
+        for (account, amount) in payouts.iter() {
+            if account != &exec::program_id() && price > MINIMUM_VALUE.into() {
+                msg::send(*account, MarketEvent::TransferValue, *amount)
+                    .expect("Error in sending value");
+            }
+        }
+    }
+
+    if nft_transfer(tx_id, nft_contract_id, &winner, token_id)
+        .await
+        .is_err()
+    {
+        return Err(MarketErr::RerunTransaction);
+    }
+
+    item.tx = None;
+    item.sealed_auction = None;
+    item.owner = winner;
+
+    Ok(MarketEvent::AuctionSettled {
+        nft_contract_id: *nft_contract_id,
+        token_id,
+        price,
+    })
+}
+
+/// Returns the winner of the sealed-bid auction and the price to pay.
+/// If there are several highest bids, the bidder with the lowest [`ActorId`] wins.
+fn sealed_auction_winner(auction: &SealedAuction) -> Option<(ActorId, Price)> {
+    let mut winner: Option<(ActorId, Price)> = None;
+    let mut second_price = auction.min_price;
+
+    for (account, bid) in auction.bids.iter() {
+        let Some(price) = bid.revealed_price else {
+            continue;
+        };
+
+        match winner {
+            Some((_, winner_price)) if price <= winner_price => {
+                second_price = second_price.max(price);
+            }
+            _ => {
+                if let Some((_, winner_price)) = winner {
+                    second_price = second_price.max(winner_price);
+                }
+                winner = Some((*account, price));
+            }
+        }
+    }
+
+    winner.map(|(account, price)| match auction.pricing {
+        SealedBidPricing::FirstPrice => (account, price),
+        SealedBidPricing::SecondPrice => (account, second_price),
+    })
+}
@@ -0,0 +1,330 @@
+use crate::{
+    contract::{refund_value, MarketHandler},
+    nft_messages::*,
+    payment::*,
+    trade::{check_tokens, set_progress},
+};
+use gstd::{exec, msg, prelude::*, ActorId};
+use market_io::{
+    ContractId, Market, MarketErr, MarketEvent, MarketTx, Price, Swap, SwapId, TokenId,
+    TradeProgress, TransactionId,
+};
+
+#[async_trait::async_trait]
+pub trait SwapHandler {
+    async fn propose_swap(
+        &mut self,
+        counterparty: &ActorId,
+        offered: Vec<(ContractId, TokenId)>,
+        requested: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        payment: Price,
+    ) -> Result<MarketEvent, MarketErr>;
+
+    async fn accept_swap(&mut self, swap_id: SwapId) -> Result<MarketEvent, MarketErr>;
+
+    fn cancel_swap(&mut self, swap_id: SwapId) -> Result<MarketEvent, MarketErr>;
+}
+
+#[async_trait::async_trait]
+impl SwapHandler for Market {
+    async fn propose_swap(
+        &mut self,
+        counterparty: &ActorId,
+        offered: Vec<(ContractId, TokenId)>,
+        requested: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        payment: Price,
+    ) -> Result<MarketEvent, MarketErr> {
+        self.check_approved_ft_contract(ft_contract_id);
+        let proposer = msg::source();
+
+        let tokens: BTreeSet<_> = offered.iter().chain(requested.iter()).collect();
+        if offered.is_empty()
+            || requested.is_empty()
+            || tokens.len() != offered.len() + requested.len()
+            || *counterparty == proposer
+        {
+            return Err(MarketErr::InvalidSwap);
+        }
+
+        if ft_contract_id.is_none() && msg::value() != payment {
+            return Err(MarketErr::WrongPrice);
+        }
+
+        // the escrowed payment is returned if the swap can't be proposed
+        for (nft_contract_id, token_id) in offered.iter() {
+            self.check_approved_nft_contract(nft_contract_id);
+            if get_owner(nft_contract_id, *token_id).await != proposer {
+                refund_value();
+                return Err(MarketErr::InvalidCaller);
+            }
+        }
+        for (nft_contract_id, token_id) in requested.iter() {
+            self.check_approved_nft_contract(nft_contract_id);
+            if get_owner(nft_contract_id, *token_id).await != *counterparty {
+                refund_value();
+                return Err(MarketErr::InvalidSwap);
+            }
+        }
+
+        let swap_id = self.swap_id;
+        self.swap_id = self.swap_id.wrapping_add(1);
+        self.swaps.insert(
+            swap_id,
+            Swap {
+                proposer,
+                counterparty: *counterparty,
+                offered,
+                requested,
+                ft_contract_id,
+                payment,
+                tx: None,
+            },
+        );
+
+        Ok(MarketEvent::SwapProposed {
+            swap_id,
+            proposer,
+            counterparty: *counterparty,
+        })
+    }
+
+    async fn accept_swap(&mut self, swap_id: SwapId) -> Result<MarketEvent, MarketErr> {
+        if let Some(swap) = self.swaps.get(&swap_id).filter(|swap| swap.tx.is_none()) {
+            for (nft_contract_id, _) in swap.offered.iter().chain(swap.requested.iter()) {
+                self.check_trading_allowed(nft_contract_id, swap.ft_contract_id)?;
+            }
+            // the NFTs of both sides could be put on an auction or transferred after the proposal
+            check_tokens(self, &swap.offered, &swap.proposer).await?;
+            check_tokens(self, &swap.requested, &swap.counterparty).await?;
+        }
+
+        let swap = self
+            .swaps
+            .get_mut(&swap_id)
+            .ok_or(MarketErr::SwapDoesNotExists)?;
+
+        if swap.counterparty != msg::source() {
+            return Err(MarketErr::InvalidCaller);
+        }
+
+        let tx_id = match &swap.tx {
+            Some((tx_id, MarketTx::Swap { .. })) => *tx_id,
+            Some(_) => return Err(MarketErr::WrongTransaction),
+            None => {
+                // each NFT can be transferred three times: to the marketplace, back to the owner and to the new owner
+                let tx_id = self.tx_id;
+                self.tx_id = self
+                    .tx_id
+                    .wrapping_add(3 * (swap.offered.len() + swap.requested.len()) as u64 + 1);
+                swap.tx = Some((
+                    tx_id,
+                    MarketTx::Swap {
+                        progress: TradeProgress::Locked(0),
+                    },
+                ));
+                tx_id
+            }
+        };
+
+        let result = accept_swap_tx(tx_id, swap_id, swap).await;
+
+        if result.is_ok() {
+            let swap = self.swaps.remove(&swap_id).expect("Can't be None");
+            let new_owners = swap
+                .offered
+                .iter()
+                .map(|token| (token, swap.counterparty))
+                .chain(swap.requested.iter().map(|token| (token, swap.proposer)));
+            for (contract_and_token_id, new_owner) in new_owners {
+                if let Some(item) = self.items.get_mut(contract_and_token_id) {
+                    item.owner = new_owner;
+                    item.price = None;
+                    item.expires_at = None;
+                    item.dutch_auction = None;
+                }
+            }
+        }
+
+        result
+    }
+
+    fn cancel_swap(&mut self, swap_id: SwapId) -> Result<MarketEvent, MarketErr> {
+        let swap = self
+            .swaps
+            .get(&swap_id)
+            .ok_or(MarketErr::SwapDoesNotExists)?;
+
+        if swap.proposer != msg::source() && swap.counterparty != msg::source() {
+            return Err(MarketErr::InvalidCaller);
+        }
+
+        if swap.tx.is_some() {
+            return Err(MarketErr::WrongTransaction);
+        }
+
+        if swap.ft_contract_id.is_none() && swap.payment > 0 {
+            msg::send(swap.proposer, MarketEvent::TransferValue, swap.payment)
+                .expect("Error in sending value");
+        }
+        self.swaps.remove(&swap_id);
+
+        Ok(MarketEvent::SwapCancelled { swap_id })
+    }
+}
+
+/// Exchanges the NFTs in steps recorded in the transaction:
+/// first all NFTs are locked on the marketplace account and the proposer pays,
+/// then the NFTs are delivered to their new owners.
+/// If a step fails before the payment, the locked NFTs are returned to their owners.
+/// The rerun continues from the step at which the transaction stopped.
+///
+/// Transaction ids of the steps for `n` NFTs of both sides:
+/// * `tx_id..tx_id + n` - transfers of NFTs to the marketplace account
+/// * `tx_id + n` - the payment in fungible tokens
+/// * `tx_id + n + 1..tx_id + 2n + 1` - returns of NFTs to their owners
+/// * `tx_id + 2n + 1..tx_id + 3n + 1` - transfers of NFTs to their new owners
+async fn accept_swap_tx(
+    tx_id: TransactionId,
+    swap_id: SwapId,
+    swap: &mut Swap,
+) -> Result<MarketEvent, MarketErr> {
+    let Some((_, MarketTx::Swap { mut progress })) = swap.tx.clone() else {
+        return Err(MarketErr::WrongTransaction);
+    };
+    let offered_count = swap.offered.len();
+    let tokens_count = (offered_count + swap.requested.len()) as u64;
+    let tokens: Vec<(ContractId, TokenId)> = swap
+        .offered
+        .iter()
+        .chain(swap.requested.iter())
+        .copied()
+        .collect();
+
+    if let TradeProgress::Locked(locked) = progress {
+        // transfer NFTs of both sides to the marketplace account
+        for (i, (nft_contract_id, token_id)) in tokens.iter().enumerate().skip(locked as usize) {
+            if nft_transfer(
+                tx_id.wrapping_add(i as u64),
+                nft_contract_id,
+                &exec::program_id(),
+                *token_id,
+            )
+            .await
+            .is_err()
+            {
+                progress = TradeProgress::Reverted {
+                    locked: i as u32,
+                    returned: 0,
+                };
+                break;
+            }
+            progress = TradeProgress::Locked(i as u32 + 1);
+            set_progress(&mut swap.tx, progress);
+        }
+
+        if progress == TradeProgress::Locked(tokens_count as u32) {
+            // the native value is attached to the swap proposal
+            progress = match swap.ft_contract_id {
+                Some(ft_id) if swap.payment > 0 => {
+                    match transfer_tokens(
+                        tx_id.wrapping_add(tokens_count),
+                        &ft_id,
+                        &swap.proposer,
+                        &swap.counterparty,
+                        swap.payment,
+                    )
+                    .await
+                    {
+                        Ok(()) => TradeProgress::Paid,
+                        Err(()) => TradeProgress::Reverted {
+                            locked: tokens_count as u32,
+                            returned: 0,
+                        },
+                    }
+                }
+                _ => TradeProgress::Paid,
+            };
+        }
+        set_progress(&mut swap.tx, progress);
+    }
+
+    if let TradeProgress::Reverted { locked, returned } = progress {
+        // return NFTs to their owners
+        for (i, (nft_contract_id, token_id)) in tokens
+            .iter()
+            .enumerate()
+            .take(locked as usize)
+            .skip(returned as usize)
+        {
+            let owner = if i < offered_count {
+                swap.proposer
+            } else {
+                swap.counterparty
+            };
+            if nft_transfer(
+                tx_id.wrapping_add(tokens_count + 1 + i as u64),
+                nft_contract_id,
+                &owner,
+                *token_id,
+            )
+            .await
+            .is_err()
+            {
+                return Err(MarketErr::RerunTransaction);
+            }
+            set_progress(
+                &mut swap.tx,
+                TradeProgress::Reverted {
+                    locked,
+                    returned: i as u32 + 1,
+                },
+            );
+        }
+        swap.tx = None;
+
+        // all NFTs are locked only if the proposer failed to pay
+        return if locked as u64 == tokens_count {
+            Err(MarketErr::TokenTransferFailed)
+        } else {
+            Err(MarketErr::NFTTransferFailed)
+        };
+    }
+
+    if progress == TradeProgress::Paid {
+        if swap.ft_contract_id.is_none() && swap.payment > 0 {
+            msg::send(swap.counterparty, MarketEvent::TransferValue, swap.payment)
+                .expect("Error in sending value");
+        }
+        progress = TradeProgress::Delivered(0);
+        set_progress(&mut swap.tx, progress);
+    }
+
+    if let TradeProgress::Delivered(delivered) = progress {
+        // transfer NFTs to their new owners
+        for (i, (nft_contract_id, token_id)) in tokens.iter().enumerate().skip(delivered as usize) {
+            let new_owner = if i < offered_count {
+                swap.counterparty
+            } else {
+                swap.proposer
+            };
+            if nft_transfer(
+                tx_id.wrapping_add(2 * tokens_count + 1 + i as u64),
+                nft_contract_id,
+                &new_owner,
+                *token_id,
+            )
+            .await
+            .is_err()
+            {
+                return Err(MarketErr::RerunTransaction);
+            }
+            set_progress(&mut swap.tx, TradeProgress::Delivered(i as u32 + 1));
+        }
+    }
+
+    swap.tx = None;
+
+    Ok(MarketEvent::SwapCompleted { swap_id })
+}
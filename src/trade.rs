@@ -0,0 +1,47 @@
+use crate::nft_messages::get_owner;
+use gstd::ActorId;
+use market_io::{ContractId, Market, MarketErr, MarketTx, TokenId, TradeProgress, TransactionId};
+
+/// Checks that the NFTs traded at once, like a bundle or a swap, can still be traded by the owner:
+/// they aren't on an auction, aren't used in another transaction and are still owned by the owner.
+pub async fn check_tokens(
+    market: &Market,
+    tokens: &[(ContractId, TokenId)],
+    owner: &ActorId,
+) -> Result<(), MarketErr> {
+    for contract_and_token_id in tokens.iter() {
+        if let Some(item) = market.items.get(contract_and_token_id) {
+            if item.auction.is_some() || item.sealed_auction.is_some() {
+                return Err(MarketErr::ItemOnAuction);
+            }
+            if item.tx.is_some() {
+                return Err(MarketErr::WrongTransaction);
+            }
+        }
+    }
+
+    for (nft_contract_id, token_id) in tokens.iter() {
+        if get_owner(nft_contract_id, *token_id).await != *owner {
+            return Err(MarketErr::ItemOwnerChanged);
+        }
+    }
+
+    Ok(())
+}
+
+/// Records the progress of the bundle sale or the swap in its transaction.
+pub fn set_progress(tx: &mut Option<(TransactionId, MarketTx)>, progress: TradeProgress) {
+    if let Some((
+        _,
+        MarketTx::BundleSale {
+            progress: tx_progress,
+            ..
+        }
+        | MarketTx::Swap {
+            progress: tx_progress,
+        },
+    )) = tx
+    {
+        *tx_progress = progress;
+    }
+}
@@ -12,6 +12,10 @@ pub mod metafns {
         market_io::all_items(state)
     }
 
+    pub fn active_items(state: State, timestamp: u64) -> Vec<Item> {
+        market_io::active_items(state, timestamp)
+    }
+
     pub fn item_info(state: State, args: ItemInfoArgs) -> Option<Item> {
         market_io::item_info(state, &args)
     }
@@ -0,0 +1,328 @@
+pub mod utils;
+
+use market_io::*;
+use utils::prelude::*;
+
+#[test]
+fn admin_handover() {
+    let system = utils::initialize_system();
+
+    let (ft_program, _, market) = utils::initialize_programs(&system);
+
+    // Must fail since only the admin can propose the new admin
+    market
+        .propose_admin(PARTICIPANTS[0], PARTICIPANTS[0].into())
+        .failed(MarketErr::InvalidCaller);
+
+    market
+        .propose_admin(ADMIN, PARTICIPANTS[0].into())
+        .succeed(PARTICIPANTS[0].into());
+
+    // Must fail since only the proposed account can accept the role
+    market
+        .accept_admin(PARTICIPANTS[1])
+        .failed(MarketErr::InvalidCaller);
+
+    market
+        .accept_admin(PARTICIPANTS[0])
+        .succeed(PARTICIPANTS[0].into());
+
+    let market_state = market.meta_state().state().0;
+    assert_eq!(market_state.admin_id, PARTICIPANTS[0].into());
+    assert_eq!(market_state.pending_admin_id, None);
+
+    // The previous admin lost the rights
+    market
+        .add_ft_contract(ADMIN, ft_program.actor_id())
+        .failed(MarketErr::MissingRole);
+    market
+        .add_ft_contract(PARTICIPANTS[0], ft_program.actor_id())
+        .succeed(ft_program.actor_id());
+}
+
+#[test]
+fn roles() {
+    let system = utils::initialize_system();
+
+    let (ft_program, _, market) = utils::initialize_programs(&system);
+
+    // Must fail since the account doesn't have the role
+    market
+        .add_ft_contract(PARTICIPANTS[0], ft_program.actor_id())
+        .failed(MarketErr::MissingRole);
+
+    // Must fail since only the account with the admin role can grant roles
+    market
+        .grant_role(
+            PARTICIPANTS[0],
+            PARTICIPANTS[0].into(),
+            Role::ContractCurator,
+        )
+        .failed(MarketErr::MissingRole);
+
+    market
+        .grant_role(ADMIN, PARTICIPANTS[0].into(), Role::ContractCurator)
+        .succeed((PARTICIPANTS[0].into(), Role::ContractCurator));
+    market
+        .add_ft_contract(PARTICIPANTS[0], ft_program.actor_id())
+        .succeed(ft_program.actor_id());
+
+    // The role is scoped, so the curator can't grant roles
+    market
+        .grant_role(PARTICIPANTS[0], PARTICIPANTS[1].into(), Role::Pauser)
+        .failed(MarketErr::MissingRole);
+
+    // The account with the admin role has all roles but can't propose the new admin
+    market
+        .grant_role(ADMIN, PARTICIPANTS[1].into(), Role::Admin)
+        .succeed((PARTICIPANTS[1].into(), Role::Admin));
+    market
+        .add_ft_contract(PARTICIPANTS[1], ft_program.actor_id())
+        .succeed(ft_program.actor_id());
+    market
+        .propose_admin(PARTICIPANTS[1], PARTICIPANTS[1].into())
+        .failed(MarketErr::InvalidCaller);
+
+    market
+        .revoke_role(
+            PARTICIPANTS[1],
+            PARTICIPANTS[0].into(),
+            Role::ContractCurator,
+        )
+        .succeed((PARTICIPANTS[0].into(), Role::ContractCurator));
+    market
+        .add_ft_contract(PARTICIPANTS[0], ft_program.actor_id())
+        .failed(MarketErr::MissingRole);
+
+    let market_state = market.meta_state().state().0;
+    assert_eq!(
+        market_state.roles.get(&Role::Admin),
+        Some(&BTreeSet::from([PARTICIPANTS[1].into()]))
+    );
+    assert_eq!(market_state.roles.get(&Role::ContractCurator), None);
+}
+
+#[test]
+fn treasury_fee_update() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    // Must fail since the account doesn't have the fee manager role
+    market
+        .update_treasury_fee(PARTICIPANTS[0], TREASURY_FEE)
+        .failed(MarketErr::MissingRole);
+
+    // Must fail since the fee is out of bounds
+    market
+        .update_treasury_fee(ADMIN, 0)
+        .failed(MarketErr::WrongTreasuryFee);
+    market
+        .update_treasury_fee(ADMIN, 501)
+        .failed(MarketErr::WrongTreasuryFee);
+
+    market
+        .grant_role(ADMIN, PARTICIPANTS[0].into(), Role::FeeManager)
+        .succeed((PARTICIPANTS[0].into(), Role::FeeManager));
+    market
+        .update_treasury(PARTICIPANTS[0], PARTICIPANTS[1].into())
+        .succeed(PARTICIPANTS[1].into());
+    market
+        .update_treasury_fee(PARTICIPANTS[0], 500)
+        .succeed(500);
+
+    let effective_at = system.block_timestamp() + DURATION;
+    market
+        .schedule_treasury_fee(PARTICIPANTS[0], 100, DURATION)
+        .succeed((100, effective_at));
+
+    // The current fee is charged until the new one takes effect
+    let market_state = market.meta_state().state().0;
+    assert_eq!(market_state.treasury_fee, 500);
+    assert_eq!(
+        market_state.scheduled_treasury_fee,
+        Some(ScheduledTreasuryFee {
+            fee: 100,
+            effective_at
+        })
+    );
+
+    system.spend_blocks((DURATION / 1000) as u32);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
+        .succeed((BUYER.into(), nft_program.actor_id(), TOKEN_ID.into()));
+
+    let market_state = market.meta_state().state().0;
+    assert_eq!(market_state.treasury_fee, 100);
+    assert_eq!(market_state.treasury_id, PARTICIPANTS[1].into());
+    assert_eq!(market_state.scheduled_treasury_fee, None);
+
+    let treasury_fee = NFT_PRICE * 100 / BASIS_POINTS as u128;
+    system.claim_value_from_mailbox(PARTICIPANTS[1]);
+    assert_eq!(system.balance_of(PARTICIPANTS[1]), treasury_fee);
+}
+
+#[test]
+fn collection_fees() {
+    let system = utils::initialize_system();
+
+    let (ft_program, nft_program, market) = utils::initialize_programs(&system);
+
+    // Must fail since the account doesn't have the fee manager role
+    market
+        .set_collection_fee(PARTICIPANTS[0], nft_program.actor_id(), Some(50))
+        .failed(MarketErr::MissingRole);
+
+    // Must fail since the NFT contract isn't approved
+    market
+        .set_collection_fee(ADMIN, ft_program.actor_id(), Some(50))
+        .failed(MarketErr::ContractNotApproved);
+
+    // Must fail since the fee is out of bounds
+    market
+        .set_collection_fee(ADMIN, nft_program.actor_id(), Some(501))
+        .failed(MarketErr::WrongTreasuryFee);
+
+    // The partner collection is charged 0.5%
+    market
+        .set_collection_fee(ADMIN, nft_program.actor_id(), Some(50))
+        .succeed((nft_program.actor_id(), Some(50)));
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+    ft_program.mint(BUYER, 100, NFT_PRICE);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), 0)
+        .succeed((BUYER.into(), nft_program.actor_id(), TOKEN_ID.into()));
+
+    let treasury_fee = NFT_PRICE * 50 / BASIS_POINTS as u128;
+    ft_program
+        .balance_of(SELLER)
+        .check(NFT_PRICE - treasury_fee);
+    ft_program.balance_of(TREASURY_ID).check(treasury_fee);
+
+    market
+        .set_collection_fee(ADMIN, nft_program.actor_id(), None)
+        .succeed((nft_program.actor_id(), None));
+
+    let market_state = market.meta_state().state().0;
+    assert!(market_state.collection_fees.is_empty());
+
+    // The new collection fee takes effect after the delay
+    let effective_at = system.block_timestamp() + DURATION;
+    market
+        .schedule_collection_fee(ADMIN, nft_program.actor_id(), Some(100), DURATION)
+        .succeed((nft_program.actor_id(), Some(100), effective_at));
+
+    let market_state = market.meta_state().state().0;
+    assert!(market_state.collection_fees.is_empty());
+    assert_eq!(
+        market_state
+            .scheduled_collection_fees
+            .get(&nft_program.actor_id()),
+        Some(&ScheduledCollectionFee {
+            fee: Some(100),
+            effective_at
+        })
+    );
+
+    system.spend_blocks((DURATION / 1000) as u32);
+    market
+        .update_treasury(ADMIN, TREASURY_ID.into())
+        .succeed(TREASURY_ID.into());
+
+    let market_state = market.meta_state().state().0;
+    assert_eq!(
+        market_state.collection_fees.get(&nft_program.actor_id()),
+        Some(&100)
+    );
+    assert!(market_state.scheduled_collection_fees.is_empty());
+}
+
+#[test]
+fn pause() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    // Must fail since the account doesn't have the pauser role
+    market
+        .pause(PARTICIPANTS[0], None)
+        .failed(MarketErr::MissingRole);
+
+    market.pause(ADMIN, None).succeed(None);
+
+    // Must fail since the marketplace is paused
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .failed(MarketErr::Paused);
+
+    market.unpause(ADMIN, None).succeed(None);
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+
+    market
+        .pause(ADMIN, Some(nft_program.actor_id()))
+        .succeed(Some(nft_program.actor_id()));
+
+    // Must fail since the NFT contract is paused, the attached value is returned
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
+        .failed(MarketErr::Paused);
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    // The owner can still remove the item
+    market
+        .remove_item(
+            SELLER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            OffersPolicy::Refund,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into()));
+
+    market
+        .unpause(ADMIN, Some(nft_program.actor_id()))
+        .succeed(Some(nft_program.actor_id()));
+
+    let market_state = market.meta_state().state().0;
+    assert!(!market_state.is_paused);
+    assert!(market_state.paused_nft_contracts.is_empty());
+}
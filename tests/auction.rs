@@ -1,8 +1,16 @@
 pub mod utils;
 
+use blake2_rfc::blake2b;
 use market_io::*;
 use utils::prelude::*;
 
+fn reserve_price_commitment(reserve_price: Price, salt: [u8; 32]) -> [u8; 32] {
+    blake2b::blake2b(32, &[], &(reserve_price, salt).encode())
+        .as_bytes()
+        .try_into()
+        .expect("Unexpected invalid hash length.")
+}
+
 #[test]
 fn auction_with_native_tokens() {
     let system = utils::initialize_system();
@@ -72,7 +80,7 @@ fn auction_with_native_tokens() {
             price: winner_price,
         });
 
-    let treasury_fee = winner_price * ((TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+    let treasury_fee = winner_price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
 
     // Check balance of SELLER
     system.claim_value_from_mailbox(SELLER);
@@ -197,7 +205,7 @@ fn auction_with_fungible_tokens() {
             price: winner_price,
         });
 
-    let treasury_fee = winner_price * ((TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+    let treasury_fee = winner_price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
 
     // Check balance of SELLER
     ft_program
@@ -333,3 +341,709 @@ fn auction_failures() {
         .settle_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
         .failed(MarketErr::AuctionDoesNotExists);
 }
+
+#[test]
+fn auction_extension() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+
+    market
+        .create_auction(
+            &system,
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    let auction = || {
+        market
+            .meta_state()
+            .state()
+            .0
+            .items
+            .get(&(nft_program.actor_id(), TokenId::from(TOKEN_ID)))
+            .expect("Unexpected invalid item.")
+            .auction
+            .clone()
+            .expect("Unexpected invalid auction.")
+    };
+    let initial_end = auction().ended_at;
+    assert_eq!(auction().max_ended_at, initial_end + MAX_EXTENSION);
+
+    // Bids made in the last `BID_PERIOD` extend the auction
+    system.spend_blocks(((DURATION - BID_PERIOD / 2) / 1000) as u32);
+    let mut ended_at = initial_end;
+    for (i, &participant) in PARTICIPANTS[..3].iter().enumerate() {
+        if i != 0 {
+            system.spend_blocks(((BID_PERIOD - MIN_BID_PERIOD) / 1000) as u32);
+        }
+
+        let bid_price = (i as u128 + 2) * NFT_PRICE;
+        system.mint_to(participant, bid_price);
+        let result = market.add_bid_extending(
+            participant,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid_price,
+            bid_price,
+        );
+        let new_end = auction().ended_at;
+        assert!(new_end > ended_at);
+        result.succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price, new_end));
+        ended_at = new_end;
+    }
+
+    // The auction can't be extended beyond `MAX_EXTENSION`
+    assert_eq!(ended_at, initial_end + MAX_EXTENSION);
+
+    let bid_price = 5 * NFT_PRICE;
+    system.mint_to(PARTICIPANTS[3], bid_price);
+    market
+        .add_bid(
+            PARTICIPANTS[3],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid_price,
+            bid_price,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price));
+    assert_eq!(auction().ended_at, ended_at);
+
+    // Must fail since the auction was extended
+    market
+        .settle_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .failed(MarketErr::AuctionIsNotOver);
+
+    system.spend_blocks((BID_PERIOD / 1000) as u32);
+
+    market
+        .settle_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed(MarketEvent::AuctionSettled {
+            nft_contract_id: nft_program.actor_id(),
+            token_id: TOKEN_ID.into(),
+            price: bid_price,
+        });
+}
+
+#[test]
+fn auction_reserve_price() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+
+    let reserve_price = 5 * NFT_PRICE;
+    let salt = [1; 32];
+    let commitment = reserve_price_commitment(reserve_price, salt);
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (Some(commitment), None),
+            BidIncrement::default(),
+            false,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    // Only the commitment to the reserve price is in the marketplace state
+    let market_state = market.meta_state().state().0;
+    let auction = market_state.items[&(nft_program.actor_id(), TOKEN_ID.into())]
+        .auction
+        .clone()
+        .expect("Unexpected invalid auction.");
+    assert_eq!(auction.reserve_price_commitment, Some(commitment));
+    assert_eq!(auction.reserve_price, None);
+
+    let bid_price = reserve_price - NFT_PRICE;
+    system.mint_to(BUYER, bid_price);
+    market
+        .add_bid(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid_price,
+            bid_price,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price));
+
+    system.spend_blocks((DURATION / 1000) as u32);
+
+    // The reserve price wasn't revealed, so the auction is cancelled
+    market
+        .settle_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed(MarketEvent::AuctionCancelled {
+            nft_contract_id: nft_program.actor_id(),
+            token_id: TOKEN_ID.into(),
+        });
+
+    // Check that the bid was returned to the bidder
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), bid_price);
+
+    // Check that the NFT was returned to the seller
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(SELLER.into());
+
+    nft_program.approve(10, SELLER, market.actor_id(), TOKEN_ID.into());
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (Some(commitment), None),
+            BidIncrement::default(),
+            false,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    // Must fail since only the owner can reveal the reserve price
+    market
+        .reveal_reserve_price(
+            BUYER,
+            (nft_program.actor_id(), TOKEN_ID.into()),
+            reserve_price,
+            salt,
+        )
+        .failed(MarketErr::InvalidCaller);
+
+    // Must fail since the reserve price doesn't match the commitment
+    market
+        .reveal_reserve_price(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into()),
+            bid_price,
+            salt,
+        )
+        .failed(MarketErr::WrongCommitment);
+
+    market
+        .reveal_reserve_price(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into()),
+            reserve_price,
+            salt,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), reserve_price));
+
+    market
+        .add_bid(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid_price,
+            bid_price,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price));
+
+    system.spend_blocks((DURATION / 1000) as u32);
+
+    // The revealed reserve price wasn't reached, so the auction is cancelled
+    market
+        .settle_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed(MarketEvent::AuctionCancelled {
+            nft_contract_id: nft_program.actor_id(),
+            token_id: TOKEN_ID.into(),
+        });
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), bid_price);
+
+    nft_program.approve(11, SELLER, market.actor_id(), TOKEN_ID.into());
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (Some(commitment), None),
+            BidIncrement::default(),
+            false,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+    market
+        .reveal_reserve_price(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into()),
+            reserve_price,
+            salt,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), reserve_price));
+
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_bid(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            reserve_price,
+            reserve_price,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), reserve_price));
+
+    system.spend_blocks((DURATION / 1000) as u32);
+
+    // The reserve price was reached, so the NFT is sold
+    market
+        .settle_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed(MarketEvent::AuctionSettled {
+            nft_contract_id: nft_program.actor_id(),
+            token_id: TOKEN_ID.into(),
+            price: reserve_price,
+        });
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+}
+
+#[test]
+fn auction_buy_now() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+
+    // Must fail since the buy-now price isn't greater than the minimum price
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (None, Some(NFT_PRICE)),
+            BidIncrement::default(),
+            false,
+            BID_PERIOD,
+            DURATION,
+        )
+        .failed(MarketErr::WrongPrice);
+
+    // The buy-now price doesn't have to satisfy the bid increment
+    let buy_now_price = 3 * NFT_PRICE;
+    let bid_increment = BidIncrement {
+        absolute: 2 * NFT_PRICE,
+        basis_points: 0,
+    };
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (None, Some(buy_now_price)),
+            bid_increment,
+            false,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    let bid_price = 2 * NFT_PRICE;
+    system.mint_to(PARTICIPANTS[0], bid_price);
+    market
+        .add_bid(
+            PARTICIPANTS[0],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid_price,
+            bid_price,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price));
+
+    system.mint_to(BUYER, buy_now_price);
+    market
+        .buy_now(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            buy_now_price,
+        )
+        .succeed(MarketEvent::AuctionSettled {
+            nft_contract_id: nft_program.actor_id(),
+            token_id: TOKEN_ID.into(),
+            price: buy_now_price,
+        });
+
+    // Check that the previous bid was returned
+    system.claim_value_from_mailbox(PARTICIPANTS[0]);
+    assert_eq!(system.balance_of(PARTICIPANTS[0]), bid_price);
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+
+    // Must fail since the auction is over
+    market
+        .buy_now(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            buy_now_price,
+        )
+        .failed(MarketErr::AuctionDoesNotExists);
+}
+
+#[test]
+fn auction_bid_increment() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+
+    // 10% of the current price, but not less than `NFT_PRICE / 2`
+    let bid_increment = BidIncrement {
+        absolute: NFT_PRICE / 2,
+        basis_points: 1_000,
+    };
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (None, None),
+            bid_increment,
+            false,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    // The first bid only has to exceed the minimum price
+    let first_bid = NFT_PRICE + 1;
+    system.mint_to(PARTICIPANTS[0], first_bid);
+    market
+        .add_bid(
+            PARTICIPANTS[0],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            first_bid,
+            first_bid,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), first_bid));
+
+    // Must fail since the bid doesn't exceed the absolute increment
+    let low_bid = first_bid + NFT_PRICE / 2 - 1;
+    market
+        .add_bid(
+            PARTICIPANTS[1],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            low_bid,
+            0,
+        )
+        .failed(MarketErr::BidIncrementTooLow);
+
+    let bid = first_bid + NFT_PRICE / 2;
+    system.mint_to(PARTICIPANTS[1], bid);
+    market
+        .add_bid(
+            PARTICIPANTS[1],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid,
+            bid,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), bid));
+}
+
+#[test]
+fn auction_cancellation() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+
+    let create_auction = || {
+        market
+            .create_auction(
+                &system,
+                SELLER,
+                (nft_program.actor_id(), TOKEN_ID.into(), None),
+                NFT_PRICE,
+                BID_PERIOD,
+                DURATION,
+            )
+            .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+    };
+    create_auction();
+
+    // Must fail since only the owner can cancel the auction
+    market
+        .cancel_auction(BUYER, nft_program.actor_id(), TOKEN_ID.into())
+        .failed(MarketErr::InvalidCaller);
+
+    // The owner can cancel the auction without bids
+    market
+        .cancel_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed((nft_program.actor_id(), TOKEN_ID.into()));
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(SELLER.into());
+
+    nft_program.approve(3, SELLER, market.actor_id(), TOKEN_ID.into());
+    create_auction();
+
+    let bid_price = 2 * NFT_PRICE;
+    system.mint_to(BUYER, bid_price);
+    market
+        .add_bid(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid_price,
+            bid_price,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price));
+
+    // Must fail since the auction already has a bid
+    market
+        .cancel_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .failed(MarketErr::AuctionHasBids);
+
+    // The admin can cancel the auction with bids
+    market
+        .cancel_auction(ADMIN, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed((nft_program.actor_id(), TOKEN_ID.into()));
+
+    // Check that the bid was returned to the bidder
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), bid_price);
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(SELLER.into());
+}
+
+#[test]
+fn auction_auto_settlement() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (None, None),
+            BidIncrement::default(),
+            true,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    let item = || {
+        market
+            .meta_state()
+            .state()
+            .0
+            .items
+            .get(&(nft_program.actor_id(), TokenId::from(TOKEN_ID)))
+            .cloned()
+            .expect("Unexpected invalid item.")
+    };
+    let auction = item().auction.expect("Unexpected invalid auction.");
+    assert!(auction.auto_settlement.is_some());
+
+    let bid_price = 2 * NFT_PRICE;
+    system.mint_to(BUYER, bid_price);
+    market
+        .add_bid(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bid_price,
+            bid_price,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price));
+
+    // The auction is settled without `MarketAction::SettleAuction` from users
+    system.spend_blocks((DURATION / 1000 + 1) as u32);
+
+    assert!(item().auction.is_none());
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+
+    let treasury_fee = bid_price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), bid_price - treasury_fee);
+
+    // The manual settlement fails since the auction was already settled
+    market
+        .settle_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .failed(MarketErr::AuctionDoesNotExists);
+}
+
+#[test]
+fn auction_auto_settlement_rescheduling() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+
+    market
+        .create_auction_with_prices(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            (None, None),
+            BidIncrement::default(),
+            true,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    let auction = || {
+        market
+            .meta_state()
+            .state()
+            .0
+            .items
+            .get(&(nft_program.actor_id(), TokenId::from(TOKEN_ID)))
+            .cloned()
+            .expect("Unexpected invalid item.")
+            .auction
+    };
+    let auto_settlement = auction()
+        .expect("Unexpected invalid auction.")
+        .auto_settlement;
+
+    // The bid within the last bid period extends the auction
+    system.spend_blocks(((DURATION - BID_PERIOD / 2) / 1000) as u32);
+    let bid_price = 2 * NFT_PRICE;
+    system.mint_to(BUYER, bid_price);
+    let result = market.add_bid_extending(
+        BUYER,
+        nft_program.actor_id(),
+        TOKEN_ID.into(),
+        bid_price,
+        bid_price,
+    );
+    let new_end = auction().expect("Unexpected invalid auction.").ended_at;
+    result.succeed((nft_program.actor_id(), TOKEN_ID.into(), bid_price, new_end));
+
+    // The bid doesn't reserve gas for another settlement
+    assert_eq!(
+        auction()
+            .expect("Unexpected invalid auction.")
+            .auto_settlement,
+        auto_settlement
+    );
+
+    // The settlement arrives before the new end and is rescheduled
+    system.spend_blocks((BID_PERIOD / 2 / 1000 + 1) as u32);
+    let rescheduled = auction()
+        .expect("Unexpected invalid auction.")
+        .auto_settlement;
+    assert!(rescheduled.is_some());
+    assert_ne!(rescheduled, auto_settlement);
+
+    system.spend_blocks((BID_PERIOD / 2 / 1000 + 1) as u32);
+    assert!(auction().is_none());
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+}
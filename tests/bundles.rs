@@ -0,0 +1,148 @@
+pub mod utils;
+
+use market_io::*;
+use utils::prelude::*;
+
+#[test]
+fn bundles() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    // the second NFT of the bundle is from another collection
+    let gear_program = utils::NonFungibleToken::initialize(&system);
+    gear_program.add_minter(0, SELLER);
+    gear_program.mint(1, SELLER);
+    gear_program.approve(2, SELLER, market.actor_id(), TOKEN_ID.into());
+    market
+        .add_nft_contract(ADMIN, gear_program.actor_id())
+        .succeed(gear_program.actor_id());
+
+    let tokens = vec![
+        (nft_program.actor_id(), TOKEN_ID.into()),
+        (gear_program.actor_id(), TOKEN_ID.into()),
+    ];
+
+    // Must fail since the bundle is empty
+    market
+        .add_bundle(SELLER, vec![], None, NFT_PRICE)
+        .failed(MarketErr::InvalidBundle);
+
+    // Must fail since the bundle contains the same NFT twice
+    market
+        .add_bundle(SELLER, vec![tokens[0], tokens[0]], None, NFT_PRICE)
+        .failed(MarketErr::InvalidBundle);
+
+    // Must fail since only the owner of the NFTs can list them
+    market
+        .add_bundle(BUYER, tokens.clone(), None, NFT_PRICE)
+        .failed(MarketErr::InvalidCaller);
+
+    market
+        .add_bundle(SELLER, tokens.clone(), None, NFT_PRICE)
+        .succeed((0, SELLER.into(), NFT_PRICE));
+
+    // Must fail since the attached value is less than the price
+    market.buy_bundle(BUYER, 0, 0).failed(MarketErr::WrongPrice);
+
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .buy_bundle(BUYER, 0, NFT_PRICE)
+        .succeed((0, BUYER.into(), NFT_PRICE));
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+    gear_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+
+    let treasury_fee = NFT_PRICE * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), NFT_PRICE - treasury_fee);
+
+    // Must fail since the bundle is sold
+    market
+        .buy_bundle(BUYER, 0, 0)
+        .failed(MarketErr::BundleDoesNotExists);
+
+    // The marketplace can't transfer the second NFT, so the sale is reverted
+    nft_program.approve(3, BUYER, market.actor_id(), TOKEN_ID.into());
+    market
+        .add_bundle(BUYER, tokens.clone(), None, NFT_PRICE)
+        .succeed((1, BUYER.into(), NFT_PRICE));
+
+    system.mint_to(PARTICIPANTS[0], NFT_PRICE);
+    market
+        .buy_bundle(PARTICIPANTS[0], 1, NFT_PRICE)
+        .failed(MarketErr::NFTTransferFailed);
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+    system.claim_value_from_mailbox(PARTICIPANTS[0]);
+    assert_eq!(system.balance_of(PARTICIPANTS[0]), NFT_PRICE);
+
+    // Must fail since only the bundle owner can remove it
+    market
+        .remove_bundle(SELLER, 1)
+        .failed(MarketErr::InvalidCaller);
+
+    market.remove_bundle(BUYER, 1).succeed(1);
+    market
+        .buy_bundle(PARTICIPANTS[0], 1, 0)
+        .failed(MarketErr::BundleDoesNotExists);
+
+    // The bundle can't be sold after one of its NFTs is put on an auction
+    market
+        .add_bundle(BUYER, tokens.clone(), None, NFT_PRICE)
+        .succeed((2, BUYER.into(), NFT_PRICE));
+    nft_program.approve(4, BUYER, market.actor_id(), TOKEN_ID.into());
+    market
+        .create_auction(
+            &system,
+            BUYER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+    market
+        .buy_bundle(PARTICIPANTS[0], 2, NFT_PRICE)
+        .failed(MarketErr::ItemOnAuction);
+    system.claim_value_from_mailbox(PARTICIPANTS[0]);
+    assert_eq!(system.balance_of(PARTICIPANTS[0]), NFT_PRICE);
+}
+
+#[test]
+fn bundle_small_payouts() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    // the treasury fee of the price is too small to be sent
+    let price = NFT_PRICE / 10;
+    market
+        .add_bundle(
+            SELLER,
+            vec![(nft_program.actor_id(), TOKEN_ID.into())],
+            None,
+            price,
+        )
+        .succeed((0, SELLER.into(), price));
+
+    system.mint_to(BUYER, price);
+    market
+        .buy_bundle(BUYER, 0, price)
+        .succeed((0, BUYER.into(), price));
+
+    // The small payout is paid to the seller
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), price);
+    system.claim_value_from_mailbox(TREASURY_ID);
+    assert_eq!(system.balance_of(TREASURY_ID), 0);
+}
@@ -0,0 +1,302 @@
+pub mod utils;
+
+use market_io::*;
+use utils::prelude::*;
+
+#[test]
+fn collection_offers() {
+    let system = utils::initialize_system();
+
+    let (ft_program, nft_program, market) = utils::initialize_programs(&system);
+
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_collection_offer(BUYER, nft_program.actor_id(), None, NFT_PRICE, NFT_PRICE)
+        .succeed((nft_program.actor_id(), None, NFT_PRICE));
+
+    // Must fail since no value is attached
+    market
+        .add_collection_offer(BUYER, nft_program.actor_id(), None, NFT_PRICE, 0)
+        .failed(MarketErr::WrongPrice);
+
+    ft_program.mint(100, BUYER, NFT_PRICE);
+    market
+        .add_collection_offer(
+            BUYER,
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            NFT_PRICE,
+            0,
+        )
+        .succeed((
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            NFT_PRICE,
+        ));
+
+    // Must fail since only the NFT owner can accept the offer
+    market
+        .accept_collection_offer(
+            BUYER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .failed(MarketErr::OfferShouldAcceptedByOwner);
+
+    // The token doesn't have to be listed on the marketplace
+    market
+        .accept_collection_offer(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            BUYER.into(),
+            NFT_PRICE,
+        ));
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+
+    let treasury_fee = NFT_PRICE * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), NFT_PRICE - treasury_fee);
+
+    // Must fail since the offer was accepted
+    market
+        .withdraw_collection_offer(BUYER, nft_program.actor_id(), None, NFT_PRICE)
+        .failed(MarketErr::OfferIsNotExists);
+
+    market
+        .withdraw_collection_offer(
+            BUYER,
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            NFT_PRICE,
+        )
+        .succeed((
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            NFT_PRICE,
+        ));
+    ft_program.balance_of(BUYER).check(NFT_PRICE);
+}
+
+#[test]
+fn expired_collection_offers_cleanup() {
+    let system = utils::initialize_system();
+
+    let (ft_program, nft_program, market) = utils::initialize_programs(&system);
+
+    let expires_at = Some(system.block_timestamp() + BID_PERIOD);
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_collection_offer_expiring(
+            BUYER,
+            (nft_program.actor_id(), None),
+            NFT_PRICE,
+            (expires_at, vec![]),
+            NFT_PRICE,
+        )
+        .succeed((nft_program.actor_id(), None, NFT_PRICE));
+
+    ft_program.mint(100, BUYER, NFT_PRICE);
+    market
+        .add_collection_offer_expiring(
+            BUYER,
+            (nft_program.actor_id(), Some(ft_program.actor_id())),
+            NFT_PRICE,
+            (expires_at, vec![]),
+            0,
+        )
+        .succeed((
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            NFT_PRICE,
+        ));
+    ft_program.balance_of(BUYER).check(0);
+
+    // Nothing to clean up yet
+    market
+        .cleanup_expired_collection_offers(SELLER, nft_program.actor_id())
+        .succeed((nft_program.actor_id(), 0));
+
+    system.spend_blocks((BID_PERIOD / 1000 + 1) as u32);
+
+    // Must fail since the offer has expired
+    market
+        .accept_collection_offer(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .failed(MarketErr::OfferExpired);
+
+    // Anyone can return the expired collection offers to their creators
+    market
+        .cleanup_expired_collection_offers(SELLER, nft_program.actor_id())
+        .succeed((nft_program.actor_id(), 2));
+
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+    ft_program.balance_of(BUYER).check(NFT_PRICE);
+
+    let market_state = market.meta_state().state().0;
+    assert!(market_state
+        .collection_offers
+        .get(&nft_program.actor_id())
+        .expect("Unexpected invalid collection offers.")
+        .offers
+        .is_empty());
+}
+
+#[test]
+fn collection_offer_for_listed_item() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(2 * NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(2 * NFT_PRICE)));
+
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_collection_offer(BUYER, nft_program.actor_id(), None, NFT_PRICE, NFT_PRICE)
+        .succeed((nft_program.actor_id(), None, NFT_PRICE));
+
+    market
+        .accept_collection_offer(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            BUYER.into(),
+            NFT_PRICE,
+        ));
+
+    // The listing is cancelled by the accepted offer
+    let item = market
+        .meta_state()
+        .state()
+        .0
+        .items
+        .get(&(nft_program.actor_id(), TokenId::from(TOKEN_ID)))
+        .cloned()
+        .expect("Unexpected invalid item.");
+    assert_eq!(item.owner, BUYER.into());
+    assert_eq!(item.price, None);
+}
+
+#[test]
+fn attribute_offers() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    let gold_background = vec![("background".to_owned(), "gold".to_owned())];
+    let silver_background = vec![("background".to_owned(), "silver".to_owned())];
+
+    system.mint_to(BUYER, 5 * NFT_PRICE);
+    market
+        .add_attribute_offer(
+            BUYER,
+            (nft_program.actor_id(), None),
+            NFT_PRICE,
+            NFT_PRICE,
+            gold_background.clone(),
+        )
+        .succeed((nft_program.actor_id(), None, NFT_PRICE));
+
+    // Must fail since the NFT contract doesn't answer the attributes query
+    market
+        .accept_collection_offer(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .failed(MarketErr::AttributesNotMatched);
+
+    let attribute_token = utils::AttributeToken::initialize(
+        &system,
+        SELLER,
+        vec![
+            ("background".to_owned(), "gold".to_owned()),
+            ("eyes".to_owned(), "laser".to_owned()),
+        ],
+    );
+    market
+        .add_nft_contract(ADMIN, attribute_token.actor_id())
+        .succeed(attribute_token.actor_id());
+
+    market
+        .add_attribute_offer(
+            BUYER,
+            (attribute_token.actor_id(), None),
+            NFT_PRICE,
+            NFT_PRICE,
+            silver_background,
+        )
+        .succeed((attribute_token.actor_id(), None, NFT_PRICE));
+    market
+        .add_attribute_offer(
+            BUYER,
+            (attribute_token.actor_id(), None),
+            2 * NFT_PRICE,
+            2 * NFT_PRICE,
+            gold_background,
+        )
+        .succeed((attribute_token.actor_id(), None, 2 * NFT_PRICE));
+
+    // Must fail since the token doesn't have the silver background
+    market
+        .accept_collection_offer(
+            SELLER,
+            (attribute_token.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .failed(MarketErr::AttributesNotMatched);
+
+    market
+        .accept_collection_offer(
+            SELLER,
+            (attribute_token.actor_id(), TOKEN_ID.into(), None),
+            2 * NFT_PRICE,
+            BUYER.into(),
+        )
+        .succeed((
+            attribute_token.actor_id(),
+            TOKEN_ID.into(),
+            BUYER.into(),
+            2 * NFT_PRICE,
+        ));
+
+    attribute_token
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+
+    let treasury_fee = 2 * NFT_PRICE * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), 2 * NFT_PRICE - treasury_fee);
+}
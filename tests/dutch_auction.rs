@@ -0,0 +1,231 @@
+pub mod utils;
+
+use market_io::*;
+use utils::prelude::*;
+
+#[test]
+fn dutch_auction_with_native_tokens() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    let start_price = 2 * NFT_PRICE;
+    market
+        .create_dutch_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            (start_price, NFT_PRICE),
+            PriceDecay::Stepwise { step: DURATION / 4 },
+            DURATION,
+        )
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            start_price,
+            NFT_PRICE,
+        ));
+
+    // Must fail since the Dutch auction is on
+    market
+        .create_auction(
+            &system,
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BID_PERIOD,
+            DURATION,
+        )
+        .failed(MarketErr::AuctionIsAlreadyExists);
+
+    // Two of four price steps have passed
+    system.spend_blocks((DURATION / 2 / 1000) as u32);
+    let price = start_price - (start_price - NFT_PRICE) / 2;
+
+    system.mint_to(BUYER, start_price);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), start_price)
+        .succeed((BUYER.into(), nft_program.actor_id(), TOKEN_ID.into()));
+
+    let treasury_fee = price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+
+    // Check balance of SELLER
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), price - treasury_fee);
+
+    // Check that the BUYER has received the change
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), start_price - price);
+
+    let item = market
+        .meta_state()
+        .state()
+        .0
+        .items
+        .get(&(nft_program.actor_id(), TokenId::from(TOKEN_ID)))
+        .cloned()
+        .expect("Unexpected invalid item.");
+    assert_eq!(item.owner, BUYER.into());
+    assert!(item.dutch_auction.is_none());
+}
+
+#[test]
+fn dutch_auction_failures() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    // Must fail since the floor price is greater than the start price
+    market
+        .create_dutch_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            (NFT_PRICE, 2 * NFT_PRICE),
+            PriceDecay::Linear,
+            DURATION,
+        )
+        .failed(MarketErr::WrongPrice);
+
+    // Must fail since the floor price is equal to zero
+    market
+        .create_dutch_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            (NFT_PRICE, 0),
+            PriceDecay::Linear,
+            DURATION,
+        )
+        .failed(MarketErr::AuctionMinPriceIsZero);
+
+    // Must fail since the price step is longer than the auction
+    market
+        .create_dutch_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            (2 * NFT_PRICE, NFT_PRICE),
+            PriceDecay::Stepwise { step: DURATION + 1 },
+            DURATION,
+        )
+        .failed(MarketErr::AuctionBidPeriodOrDurationIsInvalid);
+
+    market
+        .create_dutch_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            (2 * NFT_PRICE, NFT_PRICE),
+            PriceDecay::Linear,
+            DURATION,
+        )
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            2 * NFT_PRICE,
+            NFT_PRICE,
+        ));
+
+    // Must fail since the auction is already on
+    market
+        .create_dutch_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            (2 * NFT_PRICE, NFT_PRICE),
+            PriceDecay::Linear,
+            DURATION,
+        )
+        .failed(MarketErr::AuctionIsAlreadyExists);
+
+    system.spend_blocks((DURATION / 1000) as u32 + 1);
+
+    // Must fail since the auction has already ended
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
+        .failed(MarketErr::AuctionIsAlreadyEnded);
+
+    // The ended Dutch auction doesn't block the new auction
+    nft_program.approve(0, SELLER, market.actor_id(), TOKEN_ID.into());
+    market
+        .create_auction(
+            &system,
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+}
+
+#[test]
+fn dutch_auction_small_change() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    let start_price = 2 * NFT_PRICE;
+    market
+        .create_dutch_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            (start_price, NFT_PRICE),
+            PriceDecay::Stepwise { step: DURATION / 4 },
+            DURATION,
+        )
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            start_price,
+            NFT_PRICE,
+        ));
+
+    // The change is too small to be returned, so it's paid to the seller
+    let change = MINIMUM_VALUE as u128;
+    system.mint_to(BUYER, start_price + change);
+    market
+        .buy_item(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            start_price + change,
+        )
+        .succeed((BUYER.into(), nft_program.actor_id(), TOKEN_ID.into()));
+
+    let treasury_fee = start_price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(
+        system.balance_of(SELLER),
+        start_price - treasury_fee + change
+    );
+}
@@ -1,7 +1,6 @@
 mod utils_gclient;
 
 use gstd::prelude::*;
-use nft_marketplace::contract::BASE_PERCENT;
 use utils_gclient::{
     common::{self, gear_api_from_path, init_gear_api_from_path},
     ft, marketplace, nft,
@@ -116,13 +115,14 @@ async fn success_offers_gclient() -> gclient::Result<()> {
             Some(ft_contract),
             common::TOKEN_ID.into(),
             accepted_price,
+            common::get_user_to_actor_id(common::BUYER).await?,
             false,
         )
         .await?;
     }
 
     let treasury_fee =
-        accepted_price * ((common::TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+        accepted_price * (common::TREASURY_FEE as u128) / nft_marketplace::BASIS_POINTS as u128;
 
     let mut listener = api.subscribe().await?;
 
@@ -154,7 +154,11 @@ async fn success_offers_gclient() -> gclient::Result<()> {
         .get(&(nft_contract, common::TOKEN_ID.into()))
         .expect("Unexpected invalid item.")
         .offers
-        .contains_key(&(Some(ft_contract), accepted_price)));
+        .contains_key(&(
+            Some(ft_contract),
+            accepted_price,
+            common::get_user_to_actor_id(common::BUYER).await?
+        )));
 
     {
         let buyer_api = gear_api_from_path().with(common::BUYER)?;
@@ -219,6 +223,7 @@ async fn success_offers_gclient() -> gclient::Result<()> {
             None,
             common::TOKEN_ID.into(),
             offered_value,
+            common::get_user_to_actor_id(common::SELLER).await?,
             false,
         )
         .await?;
@@ -315,6 +320,7 @@ async fn fail_offers_gclient() -> gclient::Result<()> {
             None,
             common::TOKEN_ID.into(),
             common::NFT_PRICE,
+            common::get_user_to_actor_id(common::BUYER).await?,
             true,
         )
         .await?;
@@ -332,6 +338,7 @@ async fn fail_offers_gclient() -> gclient::Result<()> {
             None,
             common::TOKEN_ID.into(),
             2 * common::NFT_PRICE,
+            common::get_user_to_actor_id(common::BUYER).await?,
             true,
         )
         .await?;
@@ -391,6 +398,7 @@ async fn fail_offers_gclient() -> gclient::Result<()> {
             None,
             common::TOKEN_ID.into(),
             common::NFT_PRICE,
+            common::get_user_to_actor_id(common::BUYER).await?,
             true,
         )
         .await?;
@@ -480,7 +488,7 @@ async fn success_buy_with_ft_gclient() -> gclient::Result<()> {
 
     let mut listener = api.subscribe().await?;
     let treasury_fee =
-        common::NFT_PRICE * ((common::TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+        common::NFT_PRICE * (common::TREASURY_FEE as u128) / nft_marketplace::BASIS_POINTS as u128;
     assert_eq!(
         ft::balance_of(
             &api,
@@ -1,6 +1,5 @@
 pub mod utils;
 
-use gstd::ActorId;
 use market_io::*;
 use utils::prelude::*;
 
@@ -21,7 +20,7 @@ fn offers() {
         )
         .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
 
-    let mut offers: BTreeMap<(Option<ContractId>, Price), ActorId> = BTreeMap::new();
+    let mut offers: BTreeMap<OfferId, Offer> = BTreeMap::new();
     for i in 0..10 {
         let offered_price = 10_000 * (i + 1) as u128;
         system.mint_to(BUYER, offered_price);
@@ -35,7 +34,14 @@ fn offers() {
                 offered_price,
             )
             .succeed((nft_program.actor_id(), None, TOKEN_ID.into(), offered_price));
-        offers.insert((None, offered_price), BUYER.into());
+        offers.insert(
+            (None, offered_price, BUYER.into()),
+            Offer {
+                expires_at: None,
+                counter_offer: None,
+                attributes: Vec::new(),
+            },
+        );
     }
     let mut tx_id: u64 = 100;
 
@@ -60,7 +66,14 @@ fn offers() {
                 TOKEN_ID.into(),
                 offered_price,
             ));
-        offers.insert((Some(ft_program.actor_id()), offered_price), BUYER.into());
+        offers.insert(
+            (Some(ft_program.actor_id()), offered_price, BUYER.into()),
+            Offer {
+                expires_at: None,
+                counter_offer: None,
+                attributes: Vec::new(),
+            },
+        );
     }
 
     let market_state = market.meta_state().state().0;
@@ -77,6 +90,7 @@ fn offers() {
             TOKEN_ID.into(),
             Some(ft_program.actor_id()),
             accepted_price,
+            BUYER.into(),
         )
         .succeed((
             nft_program.actor_id(),
@@ -85,7 +99,7 @@ fn offers() {
             accepted_price,
         ));
 
-    let treasury_fee = accepted_price * ((TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+    let treasury_fee = accepted_price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
 
     // Check balance of SELLER
     ft_program
@@ -101,7 +115,7 @@ fn offers() {
         .get(&(nft_program.actor_id(), TOKEN_ID.into()))
         .expect("Unexpected invalid item.")
         .offers
-        .contains_key(&(Some(ft_program.actor_id()), accepted_price)));
+        .contains_key(&(Some(ft_program.actor_id()), accepted_price, BUYER.into())));
 
     // Withdraw tokens
     let withdrawn_tokens = 110_000;
@@ -134,7 +148,7 @@ fn offers() {
     // Previous owner makes offer for native value
     let offered_value = 1_000_000;
     let buyer_balance = system.balance_of(BUYER);
-    let treasury_fee = offered_value * ((TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+    let treasury_fee = offered_value * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
 
     system.mint_to(SELLER, offered_value);
     market
@@ -156,6 +170,7 @@ fn offers() {
             TOKEN_ID.into(),
             None,
             offered_value,
+            SELLER.into(),
         )
         .succeed((
             nft_program.actor_id(),
@@ -269,6 +284,7 @@ fn offers_failures() {
             TOKEN_ID.into(),
             None,
             NFT_PRICE,
+            BUYER.into(),
         )
         .failed(MarketErr::OfferShouldAcceptedByOwner);
 
@@ -280,6 +296,7 @@ fn offers_failures() {
             TOKEN_ID.into(),
             None,
             2 * NFT_PRICE,
+            BUYER.into(),
         )
         .failed(MarketErr::OfferIsNotExists);
 
@@ -314,6 +331,7 @@ fn offers_failures() {
             TOKEN_ID.into(),
             None,
             NFT_PRICE,
+            BUYER.into(),
         )
         .failed(MarketErr::AuctionIsOpened);
 
@@ -328,7 +346,7 @@ fn offers_failures() {
             None,
             NFT_PRICE,
         )
-        .failed(MarketErr::InvalidCaller);
+        .failed(MarketErr::OfferIsNotExists);
 
     // Must fail since the indicated offer hash doesn't exist
     market
@@ -341,3 +359,358 @@ fn offers_failures() {
         )
         .failed(MarketErr::OfferIsNotExists);
 }
+
+#[test]
+fn expired_offers() {
+    let system = utils::initialize_system();
+
+    let (ft_program, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    // Must fail since the offer is already expired
+    market
+        .add_offer_expiring(
+            BUYER.into(),
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            Some(system.block_timestamp()),
+            0,
+        )
+        .failed(MarketErr::OfferExpired);
+
+    let expires_at = system.block_timestamp() + BID_PERIOD;
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_offer_expiring(
+            BUYER.into(),
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            Some(expires_at),
+            NFT_PRICE,
+        )
+        .succeed((nft_program.actor_id(), None, TOKEN_ID.into(), NFT_PRICE));
+
+    ft_program.mint(100, BUYER, NFT_PRICE);
+    market
+        .add_offer_expiring(
+            BUYER.into(),
+            (
+                nft_program.actor_id(),
+                TOKEN_ID.into(),
+                Some(ft_program.actor_id()),
+            ),
+            NFT_PRICE,
+            Some(expires_at),
+            0,
+        )
+        .succeed((
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            TOKEN_ID.into(),
+            NFT_PRICE,
+        ));
+
+    // Nothing to clean up yet
+    market
+        .cleanup_expired_offers(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), 0));
+
+    system.spend_blocks((BID_PERIOD / 1000 + 1) as u32);
+
+    // Must fail since the offer has expired
+    market
+        .accept_offer(
+            SELLER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .failed(MarketErr::OfferExpired);
+
+    // Anyone can return the expired offers to their creators
+    market
+        .cleanup_expired_offers(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), 2));
+
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+    ft_program.balance_of(BUYER).check(NFT_PRICE);
+
+    let market_state = market.meta_state().state().0;
+    assert!(market_state
+        .items
+        .get(&(nft_program.actor_id(), TOKEN_ID.into()))
+        .expect("Unexpected invalid item.")
+        .offers
+        .is_empty());
+}
+
+#[test]
+fn offers_with_same_price() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    // Different buyers can offer the same price
+    for &participant in PARTICIPANTS[..2].iter() {
+        system.mint_to(participant, NFT_PRICE);
+        market
+            .add_offer(
+                participant.into(),
+                nft_program.actor_id(),
+                TOKEN_ID.into(),
+                None,
+                NFT_PRICE,
+                NFT_PRICE,
+            )
+            .succeed((nft_program.actor_id(), None, TOKEN_ID.into(), NFT_PRICE));
+    }
+
+    // The withdrawn offer is removed from the item
+    market
+        .withdraw(
+            PARTICIPANTS[0].into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+    system.claim_value_from_mailbox(PARTICIPANTS[0]);
+    assert_eq!(system.balance_of(PARTICIPANTS[0]), NFT_PRICE);
+
+    market
+        .withdraw(
+            PARTICIPANTS[0].into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::OfferIsNotExists);
+
+    market
+        .accept_offer(
+            SELLER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            PARTICIPANTS[0].into(),
+        )
+        .failed(MarketErr::OfferIsNotExists);
+
+    market
+        .accept_offer(
+            SELLER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            PARTICIPANTS[1].into(),
+        )
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            PARTICIPANTS[1].into(),
+            NFT_PRICE,
+        ));
+}
+
+#[test]
+fn counter_offers() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    let item = (nft_program.actor_id(), TOKEN_ID.into(), None);
+    let counter_price = NFT_PRICE * 2;
+
+    system.mint_to(BUYER, counter_price);
+    market
+        .add_offer(
+            BUYER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .succeed((nft_program.actor_id(), None, TOKEN_ID.into(), NFT_PRICE));
+
+    // Must fail since only the item owner can counter the offer
+    market
+        .counter_offer(BUYER, item, NFT_PRICE, BUYER.into(), counter_price)
+        .failed(MarketErr::OfferShouldAcceptedByOwner);
+
+    // Must fail since the counter price is the same as the offer price
+    market
+        .counter_offer(SELLER, item, NFT_PRICE, BUYER.into(), NFT_PRICE)
+        .failed(MarketErr::WrongPrice);
+
+    market
+        .counter_offer(SELLER, item, NFT_PRICE, BUYER.into(), counter_price)
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            BUYER.into(),
+            NFT_PRICE,
+            counter_price,
+        ));
+
+    // The offer creator declines the counter-offer
+    market
+        .decline_counter_offer(BUYER, item, NFT_PRICE)
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            BUYER.into(),
+            NFT_PRICE,
+        ));
+    market
+        .decline_counter_offer(BUYER, item, NFT_PRICE)
+        .failed(MarketErr::CounterOfferIsNotExists);
+    market
+        .accept_counter_offer(BUYER, item, NFT_PRICE, 0)
+        .failed(MarketErr::CounterOfferIsNotExists);
+
+    // Must fail since the excess of the offered value is too small to be returned
+    market
+        .counter_offer(
+            SELLER,
+            item,
+            NFT_PRICE,
+            BUYER.into(),
+            NFT_PRICE - MINIMUM_VALUE as u128,
+        )
+        .failed(MarketErr::WrongPrice);
+
+    market
+        .counter_offer(SELLER, item, NFT_PRICE, BUYER.into(), counter_price)
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            BUYER.into(),
+            NFT_PRICE,
+            counter_price,
+        ));
+
+    // Must fail since the price difference isn't attached
+    market
+        .accept_counter_offer(BUYER, item, NFT_PRICE, 0)
+        .failed(MarketErr::WrongPrice);
+
+    let seller_balance = system.balance_of(SELLER);
+    let treasury_fee = counter_price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+
+    market
+        .accept_counter_offer(BUYER, item, NFT_PRICE, counter_price - NFT_PRICE)
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            BUYER.into(),
+            counter_price,
+        ));
+
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(
+        system.balance_of(SELLER),
+        seller_balance + counter_price - treasury_fee
+    );
+
+    let market_state = market.meta_state().state().0;
+    let item = market_state
+        .items
+        .get(&(nft_program.actor_id(), TOKEN_ID.into()))
+        .expect("Unexpected invalid item.");
+    assert_eq!(item.owner, BUYER.into());
+    assert!(item.offers.is_empty());
+}
+
+#[test]
+fn offer_after_owner_change() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_offer(
+            BUYER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .succeed((nft_program.actor_id(), None, TOKEN_ID.into(), NFT_PRICE));
+
+    nft_program.transfer(3, SELLER, PARTICIPANTS[0], TOKEN_ID.into());
+
+    // Must fail since the seller doesn't own the NFT anymore
+    market
+        .accept_offer(
+            SELLER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            BUYER.into(),
+        )
+        .failed(MarketErr::ItemOwnerChanged);
+
+    let item = market
+        .meta_state()
+        .state()
+        .0
+        .items
+        .get(&(nft_program.actor_id(), TokenId::from(TOKEN_ID)))
+        .cloned()
+        .expect("Unexpected invalid item.");
+    assert_eq!(item.owner, PARTICIPANTS[0].into());
+}
@@ -1,6 +1,6 @@
 pub mod utils;
 
-use market_io::MarketErr;
+use market_io::{MarketData, MarketErr, OffersPolicy, SealedBidPricing};
 use utils::prelude::*;
 
 #[test]
@@ -28,7 +28,7 @@ fn buy_with_fungible_tokens() {
         .succeed((BUYER.into(), nft_program.actor_id(), TOKEN_ID.into()));
 
     // Check balance of SELLER
-    let treasury_fee = NFT_PRICE * ((TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+    let treasury_fee = NFT_PRICE * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
     ft_program
         .balance_of(SELLER)
         .check(NFT_PRICE - treasury_fee);
@@ -79,6 +79,18 @@ fn buy_with_fungible_tokens_failures() {
     market
         .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), 0)
         .failed(MarketErr::ItemOnAuction);
+
+    // Must fail since the item can't be listed during an auction
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .failed(MarketErr::ItemOnAuction);
 }
 
 #[test]
@@ -114,7 +126,7 @@ fn buy_with_native_tokens() {
         .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
         .succeed((BUYER.into(), nft_program.actor_id(), TOKEN_ID.into()));
 
-    let treasury_fee = NFT_PRICE * ((TREASURY_FEE * BASE_PERCENT) as u128) / 10_000u128;
+    let treasury_fee = NFT_PRICE * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
 
     // Check balance of SELLER
     system.claim_value_from_mailbox(SELLER);
@@ -124,3 +136,590 @@ fn buy_with_native_tokens() {
     system.claim_value_from_mailbox(TREASURY_ID);
     assert_eq!(system.balance_of(TREASURY_ID), treasury_fee);
 }
+
+#[test]
+fn buy_expired_listing() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    // Must fail since the listing is already expired
+    market
+        .add_market_data_expiring(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            Some(NFT_PRICE),
+            Some(system.block_timestamp()),
+        )
+        .failed(MarketErr::ListingExpired);
+
+    let expires_at = system.block_timestamp() + BID_PERIOD;
+    market
+        .add_market_data_expiring(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            Some(NFT_PRICE),
+            Some(expires_at),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+
+    system.spend_blocks((BID_PERIOD / 1000 + 1) as u32);
+
+    // Must fail since the listing has expired
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), 0)
+        .failed(MarketErr::ListingExpired);
+
+    // The owner can renew the listing
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
+        .succeed((BUYER.into(), nft_program.actor_id(), TOKEN_ID.into()));
+}
+
+#[test]
+fn batch_listing() {
+    let system = utils::initialize_system();
+
+    let (ft_program, nft_program, market) = utils::initialize_programs(&system);
+
+    let other_program = utils::NonFungibleToken::initialize(&system);
+    other_program.add_minter(0, BUYER);
+    other_program.mint(1, BUYER);
+    market
+        .add_nft_contract(ADMIN, other_program.actor_id())
+        .succeed(other_program.actor_id());
+
+    let market_data = |nft_contract_id| MarketData {
+        nft_contract_id,
+        ft_contract_id: None,
+        token_id: TOKEN_ID.into(),
+        price: Some(NFT_PRICE),
+        expires_at: None,
+    };
+
+    // The items that can't be listed are skipped
+    market
+        .batch_add_market_data(
+            SELLER,
+            vec![
+                market_data(nft_program.actor_id()),
+                market_data(ft_program.actor_id()),
+                market_data(other_program.actor_id()),
+            ],
+        )
+        .succeed(vec![
+            (nft_program.actor_id(), TOKEN_ID.into(), Ok(())),
+            (
+                ft_program.actor_id(),
+                TOKEN_ID.into(),
+                Err(MarketErr::ContractNotApproved),
+            ),
+            (
+                other_program.actor_id(),
+                TOKEN_ID.into(),
+                Err(MarketErr::InvalidCaller),
+            ),
+        ]);
+
+    let market_state = market.meta_state().state().0;
+    assert_eq!(
+        market_state
+            .items
+            .get(&(nft_program.actor_id(), TOKEN_ID.into()))
+            .expect("Unexpected invalid item.")
+            .price,
+        Some(NFT_PRICE)
+    );
+    assert!(!market_state
+        .items
+        .contains_key(&(other_program.actor_id(), TOKEN_ID.into())));
+
+    market
+        .batch_remove_listings(
+            SELLER,
+            vec![
+                (nft_program.actor_id(), TOKEN_ID.into()),
+                (other_program.actor_id(), TOKEN_ID.into()),
+            ],
+        )
+        .succeed(vec![
+            (nft_program.actor_id(), TOKEN_ID.into(), Ok(())),
+            (
+                other_program.actor_id(),
+                TOKEN_ID.into(),
+                Err(MarketErr::ItemDoesNotExists),
+            ),
+        ]);
+
+    // Must fail since the item was removed from the sale
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
+        .failed(MarketErr::ItemIsNotOnSale);
+}
+
+#[test]
+fn buy_many_items() {
+    let system = utils::initialize_system();
+
+    let (ft_program, nft_program, market) = utils::initialize_programs(&system);
+
+    let other_program = utils::NonFungibleToken::initialize(&system);
+    other_program.add_minter(0, SELLER);
+    other_program.mint(1, SELLER);
+    other_program.approve(2, SELLER, market.actor_id(), TOKEN_ID.into());
+    market
+        .add_nft_contract(ADMIN, other_program.actor_id())
+        .succeed(other_program.actor_id());
+
+    for (nft_contract_id, price) in [
+        (nft_program.actor_id(), NFT_PRICE),
+        (other_program.actor_id(), NFT_PRICE * 2),
+    ] {
+        market
+            .add_market_data(
+                &system,
+                SELLER,
+                nft_contract_id,
+                None,
+                TOKEN_ID.into(),
+                Some(price),
+            )
+            .succeed((nft_contract_id, TOKEN_ID.into(), Some(price)));
+    }
+
+    let items = vec![
+        (nft_program.actor_id(), TOKEN_ID.into()),
+        (other_program.actor_id(), TOKEN_ID.into()),
+        (ft_program.actor_id(), TOKEN_ID.into()),
+    ];
+
+    // Must fail since the attached value isn't equal to the maximum total price
+    market
+        .buy_items(BUYER, items.clone(), None, NFT_PRICE * 2, 0)
+        .failed(MarketErr::WrongPrice);
+
+    // The items that can't be bought for the remaining value are skipped
+    system.mint_to(BUYER, NFT_PRICE * 2);
+    market
+        .buy_items(BUYER, items, None, NFT_PRICE * 2, NFT_PRICE * 2)
+        .succeed(vec![
+            (nft_program.actor_id(), TOKEN_ID.into(), Ok(NFT_PRICE)),
+            (
+                other_program.actor_id(),
+                TOKEN_ID.into(),
+                Err(MarketErr::SpendLimitExceeded),
+            ),
+            (
+                ft_program.actor_id(),
+                TOKEN_ID.into(),
+                Err(MarketErr::ItemDoesNotExists),
+            ),
+        ]);
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+    other_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(SELLER.into());
+
+    // The value that isn't spent is returned
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    let items = vec![(other_program.actor_id(), TOKEN_ID.into())];
+
+    // Must fail since the value is attached to the purchase for fungible tokens
+    market
+        .buy_items(
+            BUYER,
+            items.clone(),
+            Some(ft_program.actor_id()),
+            NFT_PRICE * 2,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::WrongPrice);
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    // The remainder is too small to be returned, so it's paid to the seller
+    let change = MINIMUM_VALUE as u128;
+    system.mint_to(BUYER, NFT_PRICE + change);
+    market
+        .buy_items(
+            BUYER,
+            items,
+            None,
+            NFT_PRICE * 2 + change,
+            NFT_PRICE * 2 + change,
+        )
+        .succeed(vec![(
+            other_program.actor_id(),
+            TOKEN_ID.into(),
+            Ok(NFT_PRICE * 2),
+        )]);
+
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), 0);
+    let treasury_fee = |price: u128| price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(
+        system.balance_of(SELLER),
+        NFT_PRICE - treasury_fee(NFT_PRICE) + NFT_PRICE * 2 - treasury_fee(NFT_PRICE * 2) + change
+    );
+}
+
+#[test]
+fn remove_item() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_offer(
+            BUYER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .succeed((nft_program.actor_id(), None, TOKEN_ID.into(), NFT_PRICE));
+
+    // Must fail since only the owner can remove the item
+    market
+        .remove_item(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            OffersPolicy::Refund,
+        )
+        .failed(MarketErr::InvalidCaller);
+
+    // The item with offers is kept off the sale
+    market
+        .remove_item(
+            SELLER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            OffersPolicy::Keep,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into()));
+
+    let market_state = market.meta_state().state().0;
+    let item = market_state
+        .items
+        .get(&(nft_program.actor_id(), TOKEN_ID.into()))
+        .expect("Unexpected invalid item.");
+    assert_eq!(item.price, None);
+    assert_eq!(item.offers.len(), 1);
+
+    market
+        .remove_item(
+            SELLER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            OffersPolicy::Refund,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into()));
+
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    let market_state = market.meta_state().state().0;
+    assert!(market_state.items.is_empty());
+
+    market
+        .remove_item(
+            SELLER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            OffersPolicy::Refund,
+        )
+        .failed(MarketErr::ItemDoesNotExists);
+}
+
+#[test]
+fn buy_item_transferred_outside_market() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+
+    nft_program.transfer(3, SELLER, PARTICIPANTS[0], TOKEN_ID.into());
+
+    // Must fail since the seller doesn't own the NFT anymore
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
+        .failed(MarketErr::ItemOwnerChanged);
+
+    // The attached value is returned
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    let market_state = market.meta_state().state().0;
+    let item = market_state
+        .items
+        .get(&(nft_program.actor_id(), TOKEN_ID.into()))
+        .expect("Unexpected invalid item.");
+    assert_eq!(item.owner, PARTICIPANTS[0].into());
+    assert_eq!(item.price, None);
+
+    market
+        .sync_item(BUYER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed((
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            PARTICIPANTS[0].into(),
+            false,
+        ));
+
+    // Anyone can sync the item after the NFT is transferred
+    nft_program.transfer(0, PARTICIPANTS[0], SELLER, TOKEN_ID.into());
+    market
+        .sync_item(BUYER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), SELLER.into(), true));
+}
+
+#[test]
+fn trading_after_contract_removal() {
+    let system = utils::initialize_system();
+
+    let (ft_program, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), Some(NFT_PRICE)));
+
+    system.mint_to(PARTICIPANTS[1], NFT_PRICE);
+    market
+        .add_collection_offer(
+            PARTICIPANTS[1],
+            nft_program.actor_id(),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .succeed((nft_program.actor_id(), None, NFT_PRICE));
+
+    // Must fail since only the contract curator can remove contracts
+    market
+        .remove_nft_contract(SELLER, nft_program.actor_id())
+        .failed(MarketErr::MissingRole);
+
+    market
+        .remove_nft_contract(ADMIN, nft_program.actor_id())
+        .succeed(nft_program.actor_id());
+
+    // Must fail since the NFT contract isn't approved anymore
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .buy_item(BUYER, nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE)
+        .failed(MarketErr::ContractNotApproved);
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    // Must fail since the NFTs of the removed contract can't be traded through offers
+    system.mint_to(PARTICIPANTS[0], NFT_PRICE);
+    market
+        .add_offer(
+            PARTICIPANTS[0].into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::ContractNotApproved);
+    market
+        .accept_collection_offer(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            PARTICIPANTS[1].into(),
+        )
+        .failed(MarketErr::ContractNotApproved);
+
+    // The owner can still remove the item
+    market
+        .remove_item(
+            SELLER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            OffersPolicy::Refund,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into()));
+
+    market
+        .add_nft_contract(ADMIN, nft_program.actor_id())
+        .succeed(nft_program.actor_id());
+    market
+        .remove_ft_contract(ADMIN, ft_program.actor_id())
+        .succeed(ft_program.actor_id());
+
+    // Must fail since the FT contract isn't approved anymore
+    assert!(market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            Some(ft_program.actor_id()),
+            TOKEN_ID.into(),
+            Some(NFT_PRICE),
+        )
+        .result
+        .main_failed());
+
+    let market_state = market.meta_state().state().0;
+    assert!(market_state
+        .approved_nft_contracts
+        .contains(&nft_program.actor_id()));
+    assert!(!market_state
+        .approved_ft_contracts
+        .contains(&ft_program.actor_id()));
+}
+
+#[test]
+fn value_refund_after_contract_removal() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    nft_program.mint(3, SELLER);
+    nft_program.approve(4, SELLER, market.actor_id(), 1.into());
+    nft_program.mint(5, SELLER);
+
+    for token_id in [TOKEN_ID, 1] {
+        market
+            .add_market_data(
+                &system,
+                SELLER,
+                nft_program.actor_id(),
+                None,
+                token_id.into(),
+                None,
+            )
+            .succeed((nft_program.actor_id(), token_id.into(), None));
+    }
+    market
+        .create_auction(
+            &system,
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            BID_PERIOD,
+            DURATION,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+    market
+        .create_sealed_auction(
+            SELLER,
+            (nft_program.actor_id(), 1.into(), None),
+            NFT_PRICE,
+            SealedBidPricing::FirstPrice,
+            (DURATION, DURATION),
+        )
+        .succeed((nft_program.actor_id(), 1.into(), NFT_PRICE));
+    market
+        .add_bundle(
+            SELLER,
+            vec![(nft_program.actor_id(), 2.into())],
+            None,
+            NFT_PRICE,
+        )
+        .succeed((0, SELLER.into(), NFT_PRICE));
+
+    market
+        .remove_nft_contract(ADMIN, nft_program.actor_id())
+        .succeed(nft_program.actor_id());
+
+    // The value attached to the bids, offers and purchases of the removed contract is returned
+    system.mint_to(BUYER, NFT_PRICE);
+    market
+        .add_bid(
+            BUYER,
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::ContractNotApproved);
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    market
+        .add_offer(
+            BUYER.into(),
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::ContractNotApproved);
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    market
+        .commit_bid(
+            BUYER,
+            nft_program.actor_id(),
+            1.into(),
+            [0; 32],
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::ContractNotApproved);
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    market
+        .buy_bundle(BUYER, 0, NFT_PRICE)
+        .failed(MarketErr::ContractNotApproved);
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+}
@@ -0,0 +1,160 @@
+pub mod utils;
+
+use blake2_rfc::blake2b;
+use gstd::ActorId;
+use market_io::*;
+use utils::prelude::*;
+
+const PHASE_DURATION: u64 = 600_000;
+
+fn commitment(bidder: u64, price: Price, salt: [u8; 32]) -> [u8; 32] {
+    let bidder: ActorId = bidder.into();
+
+    blake2b::blake2b(32, &[], &(bidder, price, salt).encode())
+        .as_bytes()
+        .try_into()
+        .expect("Unexpected invalid hash length.")
+}
+
+#[test]
+fn sealed_auction_with_native_tokens() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    market
+        .add_market_data(
+            &system,
+            SELLER,
+            nft_program.actor_id(),
+            None,
+            TOKEN_ID.into(),
+            None,
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), None));
+
+    market
+        .create_sealed_auction(
+            SELLER,
+            (nft_program.actor_id(), TOKEN_ID.into(), None),
+            NFT_PRICE,
+            SealedBidPricing::SecondPrice,
+            (PHASE_DURATION, PHASE_DURATION),
+        )
+        .succeed((nft_program.actor_id(), TOKEN_ID.into(), NFT_PRICE));
+
+    // (bidder, price, deposit)
+    let bids = [
+        (PARTICIPANTS[0], 3 * NFT_PRICE, 4 * NFT_PRICE),
+        (PARTICIPANTS[1], 2 * NFT_PRICE, 2 * NFT_PRICE),
+        (PARTICIPANTS[2], 5 * NFT_PRICE, 5 * NFT_PRICE),
+    ];
+    for (i, &(bidder, price, deposit)) in bids.iter().enumerate() {
+        system.mint_to(bidder, deposit);
+        market
+            .commit_bid(
+                bidder,
+                nft_program.actor_id(),
+                TOKEN_ID.into(),
+                commitment(bidder, price, [i as u8; 32]),
+                deposit,
+                deposit,
+            )
+            .succeed((nft_program.actor_id(), TOKEN_ID.into(), bidder.into()));
+    }
+
+    // Must fail since the bid is already committed
+    market
+        .commit_bid(
+            PARTICIPANTS[0],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            commitment(PARTICIPANTS[0], NFT_PRICE, [0; 32]),
+            NFT_PRICE,
+            0,
+        )
+        .failed(MarketErr::BidAlreadyCommitted);
+
+    // Must fail since the reveal phase hasn't started
+    market
+        .reveal_bid(
+            PARTICIPANTS[0],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bids[0].1,
+            [0; 32],
+        )
+        .failed(MarketErr::RevealPhaseIsNotActive);
+
+    system.spend_blocks((PHASE_DURATION / 1000) as u32);
+
+    // Must fail since the salt doesn't match the commitment
+    market
+        .reveal_bid(
+            PARTICIPANTS[1],
+            nft_program.actor_id(),
+            TOKEN_ID.into(),
+            bids[1].1,
+            [0; 32],
+        )
+        .failed(MarketErr::WrongCommitment);
+
+    // The third bidder doesn't reveal the bid
+    for (i, &(bidder, price, _)) in bids[..2].iter().enumerate() {
+        market
+            .reveal_bid(
+                bidder,
+                nft_program.actor_id(),
+                TOKEN_ID.into(),
+                price,
+                [i as u8; 32],
+            )
+            .succeed((
+                nft_program.actor_id(),
+                TOKEN_ID.into(),
+                bidder.into(),
+                price,
+            ));
+    }
+
+    // Must fail since the reveal phase isn't over
+    market
+        .settle_sealed_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .failed(MarketErr::AuctionIsNotOver);
+
+    system.spend_blocks((PHASE_DURATION / 1000) as u32);
+
+    // The winner pays the second highest price
+    let price = bids[1].1;
+    market
+        .settle_sealed_auction(SELLER, nft_program.actor_id(), TOKEN_ID.into())
+        .succeed(MarketEvent::AuctionSettled {
+            nft_contract_id: nft_program.actor_id(),
+            token_id: TOKEN_ID.into(),
+            price,
+        });
+
+    // Check that deposits were returned to bidders
+    for (i, &(bidder, _, deposit)) in bids.iter().enumerate() {
+        system.claim_value_from_mailbox(bidder);
+        let returned = if i == 0 { deposit - price } else { deposit };
+        assert_eq!(system.balance_of(bidder), returned);
+    }
+
+    let treasury_fee = price * (TREASURY_FEE as u128) / BASIS_POINTS as u128;
+
+    // Check balance of SELLER
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), price - treasury_fee);
+
+    let item = market
+        .meta_state()
+        .state()
+        .0
+        .items
+        .get(&(nft_program.actor_id(), TokenId::from(TOKEN_ID)))
+        .cloned()
+        .expect("Unexpected invalid item.");
+    assert_eq!(item.owner, PARTICIPANTS[0].into());
+    assert!(item.sealed_auction.is_none());
+}
@@ -0,0 +1,141 @@
+pub mod utils;
+
+use market_io::*;
+use utils::prelude::*;
+
+#[test]
+fn swaps() {
+    let system = utils::initialize_system();
+
+    let (_, nft_program, market) = utils::initialize_programs(&system);
+
+    // the counterparty owns the NFT of another collection
+    let gear_program = utils::NonFungibleToken::initialize(&system);
+    gear_program.add_minter(0, BUYER);
+    gear_program.mint(1, BUYER);
+    gear_program.approve(2, BUYER, market.actor_id(), TOKEN_ID.into());
+    market
+        .add_nft_contract(ADMIN, gear_program.actor_id())
+        .succeed(gear_program.actor_id());
+
+    let seller_token = (nft_program.actor_id(), TOKEN_ID.into());
+    let buyer_token = (gear_program.actor_id(), TOKEN_ID.into());
+
+    // Must fail since the counterparty is the proposer
+    market
+        .propose_swap(
+            SELLER,
+            SELLER.into(),
+            (vec![seller_token], vec![buyer_token]),
+            None,
+            0,
+            0,
+        )
+        .failed(MarketErr::InvalidSwap);
+
+    // Must fail since the proposer doesn't own the offered NFT, the payment is returned
+    system.mint_to(SELLER, NFT_PRICE);
+    market
+        .propose_swap(
+            SELLER,
+            BUYER.into(),
+            (vec![buyer_token], vec![seller_token]),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::InvalidCaller);
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), NFT_PRICE);
+
+    // Must fail since the counterparty doesn't own the requested NFT, the payment is returned
+    market
+        .propose_swap(
+            SELLER,
+            PARTICIPANTS[0].into(),
+            (vec![seller_token], vec![buyer_token]),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .failed(MarketErr::InvalidSwap);
+    system.claim_value_from_mailbox(SELLER);
+    assert_eq!(system.balance_of(SELLER), NFT_PRICE);
+
+    // The proposer adds the native value to the NFT
+    market
+        .propose_swap(
+            SELLER,
+            BUYER.into(),
+            (vec![seller_token], vec![buyer_token]),
+            None,
+            NFT_PRICE,
+            NFT_PRICE,
+        )
+        .succeed((0, SELLER.into(), BUYER.into()));
+
+    // Must fail since only the counterparty can accept the swap
+    market
+        .accept_swap(SELLER, 0)
+        .failed(MarketErr::InvalidCaller);
+
+    market.accept_swap(BUYER, 0).succeed(0);
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+    gear_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(SELLER.into());
+    system.claim_value_from_mailbox(BUYER);
+    assert_eq!(system.balance_of(BUYER), NFT_PRICE);
+
+    // The marketplace can't transfer the requested NFT, so the swap is reverted
+    nft_program.approve(3, BUYER, market.actor_id(), TOKEN_ID.into());
+    market
+        .propose_swap(
+            BUYER,
+            SELLER.into(),
+            (vec![seller_token], vec![buyer_token]),
+            None,
+            0,
+            0,
+        )
+        .succeed((1, BUYER.into(), SELLER.into()));
+    market
+        .accept_swap(SELLER, 1)
+        .failed(MarketErr::NFTTransferFailed);
+
+    nft_program
+        .meta_state()
+        .owner_id(TOKEN_ID)
+        .check(BUYER.into());
+
+    // Must fail since only the swap participants can cancel it
+    market
+        .cancel_swap(PARTICIPANTS[0], 1)
+        .failed(MarketErr::InvalidCaller);
+
+    market.cancel_swap(SELLER, 1).succeed(1);
+    market
+        .accept_swap(SELLER, 1)
+        .failed(MarketErr::SwapDoesNotExists);
+
+    // Must fail since the proposer transferred the offered NFT after the proposal
+    market
+        .propose_swap(
+            BUYER,
+            SELLER.into(),
+            (vec![seller_token], vec![buyer_token]),
+            None,
+            0,
+            0,
+        )
+        .succeed((2, BUYER.into(), SELLER.into()));
+    nft_program.transfer(4, BUYER, PARTICIPANTS[0], TOKEN_ID.into());
+    market
+        .accept_swap(SELLER, 2)
+        .failed(MarketErr::ItemOwnerChanged);
+}
@@ -62,6 +62,165 @@ impl<'a> Market<'a> {
         )
     }
 
+    pub fn remove_nft_contract(
+        &self,
+        from: u64,
+        nft_contract_id: ActorId,
+    ) -> MarketRunResult<ContractId> {
+        RunResult::new(
+            self.0
+                .send(from, MarketAction::RemoveNftContract(nft_contract_id)),
+            MarketEvent::NftContractRemoved,
+        )
+    }
+
+    pub fn remove_ft_contract(
+        &self,
+        from: u64,
+        ft_contract_id: ActorId,
+    ) -> MarketRunResult<ContractId> {
+        RunResult::new(
+            self.0
+                .send(from, MarketAction::RemoveFtContract(ft_contract_id)),
+            MarketEvent::FtContractRemoved,
+        )
+    }
+
+    pub fn update_treasury_fee(&self, from: u64, fee: u16) -> MarketRunResult<u16> {
+        RunResult::new(
+            self.0
+                .send(from, MarketAction::UpdateTreasuryFee { fee, delay: 0 }),
+            MarketEvent::TreasuryFeeUpdated,
+        )
+    }
+
+    pub fn schedule_treasury_fee(
+        &self,
+        from: u64,
+        fee: u16,
+        delay: u64,
+    ) -> MarketRunResult<(u16, u64)> {
+        RunResult::new(
+            self.0
+                .send(from, MarketAction::UpdateTreasuryFee { fee, delay }),
+            |(fee, effective_at)| MarketEvent::TreasuryFeeScheduled { fee, effective_at },
+        )
+    }
+
+    pub fn set_collection_fee(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        fee: Option<u16>,
+    ) -> MarketRunResult<(ContractId, Option<u16>)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::SetCollectionFee {
+                    nft_contract_id,
+                    fee,
+                    delay: 0,
+                },
+            ),
+            |(nft_contract_id, fee)| MarketEvent::CollectionFeeUpdated {
+                nft_contract_id,
+                fee,
+            },
+        )
+    }
+
+    pub fn schedule_collection_fee(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        fee: Option<u16>,
+        delay: u64,
+    ) -> MarketRunResult<(ContractId, Option<u16>, u64)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::SetCollectionFee {
+                    nft_contract_id,
+                    fee,
+                    delay,
+                },
+            ),
+            |(nft_contract_id, fee, effective_at)| MarketEvent::CollectionFeeScheduled {
+                nft_contract_id,
+                fee,
+                effective_at,
+            },
+        )
+    }
+
+    pub fn update_treasury(&self, from: u64, treasury_id: ActorId) -> MarketRunResult<ActorId> {
+        RunResult::new(
+            self.0.send(from, MarketAction::UpdateTreasury(treasury_id)),
+            MarketEvent::TreasuryUpdated,
+        )
+    }
+
+    pub fn pause(
+        &self,
+        from: u64,
+        nft_contract_id: Option<ContractId>,
+    ) -> MarketRunResult<Option<ContractId>> {
+        RunResult::new(
+            self.0.send(from, MarketAction::Pause { nft_contract_id }),
+            |nft_contract_id| MarketEvent::Paused { nft_contract_id },
+        )
+    }
+
+    pub fn unpause(
+        &self,
+        from: u64,
+        nft_contract_id: Option<ContractId>,
+    ) -> MarketRunResult<Option<ContractId>> {
+        RunResult::new(
+            self.0.send(from, MarketAction::Unpause { nft_contract_id }),
+            |nft_contract_id| MarketEvent::Unpaused { nft_contract_id },
+        )
+    }
+
+    pub fn propose_admin(&self, from: u64, admin_id: ActorId) -> MarketRunResult<ActorId> {
+        RunResult::new(
+            self.0.send(from, MarketAction::ProposeAdmin(admin_id)),
+            MarketEvent::AdminProposed,
+        )
+    }
+
+    pub fn accept_admin(&self, from: u64) -> MarketRunResult<ActorId> {
+        RunResult::new(
+            self.0.send(from, MarketAction::AcceptAdmin),
+            MarketEvent::AdminChanged,
+        )
+    }
+
+    pub fn grant_role(
+        &self,
+        from: u64,
+        account: ActorId,
+        role: Role,
+    ) -> MarketRunResult<(ActorId, Role)> {
+        RunResult::new(
+            self.0.send(from, MarketAction::GrantRole { account, role }),
+            |(account, role)| MarketEvent::RoleGranted { account, role },
+        )
+    }
+
+    pub fn revoke_role(
+        &self,
+        from: u64,
+        account: ActorId,
+        role: Role,
+    ) -> MarketRunResult<(ActorId, Role)> {
+        RunResult::new(
+            self.0
+                .send(from, MarketAction::RevokeRole { account, role }),
+            |(account, role)| MarketEvent::RoleRevoked { account, role },
+        )
+    }
+
     pub fn add_market_data(
         &self,
         _sys: &System,
@@ -71,99 +230,553 @@ impl<'a> Market<'a> {
         token_id: TokenId,
         price: Option<u128>,
     ) -> MarketRunResult<(ContractId, TokenId, Option<Price>)> {
+        self.add_market_data_expiring(
+            from,
+            (nft_contract_id, token_id, ft_contract_id),
+            price,
+            None,
+        )
+    }
+
+    pub fn add_market_data_expiring(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
+        price: Option<u128>,
+        expires_at: Option<u64>,
+    ) -> MarketRunResult<(ContractId, TokenId, Option<Price>)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::AddMarketData {
+                    nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    price,
+                    expires_at,
+                },
+            ),
+            |(nft_contract_id, token_id, price)| MarketEvent::MarketDataAdded {
+                nft_contract_id,
+                token_id,
+                price,
+            },
+        )
+    }
+
+    pub fn batch_add_market_data(
+        &self,
+        from: u64,
+        market_data: Vec<MarketData>,
+    ) -> MarketRunResult<Vec<(ContractId, TokenId, Result<(), MarketErr>)>> {
+        RunResult::new(
+            self.0
+                .send(from, MarketAction::BatchAddMarketData(market_data)),
+            MarketEvent::BatchResult,
+        )
+    }
+
+    pub fn batch_remove_listings(
+        &self,
+        from: u64,
+        tokens: Vec<(ContractId, TokenId)>,
+    ) -> MarketRunResult<Vec<(ContractId, TokenId, Result<(), MarketErr>)>> {
+        RunResult::new(
+            self.0.send(from, MarketAction::BatchRemoveListings(tokens)),
+            MarketEvent::BatchResult,
+        )
+    }
+
+    pub fn sync_item(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+    ) -> MarketRunResult<(ContractId, TokenId, ActorId, bool)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::SyncItem {
+                    nft_contract_id,
+                    token_id,
+                },
+            ),
+            |(nft_contract_id, token_id, owner, is_changed)| MarketEvent::ItemSynced {
+                nft_contract_id,
+                token_id,
+                owner,
+                is_changed,
+            },
+        )
+    }
+
+    pub fn remove_item(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        offers_policy: OffersPolicy,
+    ) -> MarketRunResult<(ContractId, TokenId)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::RemoveItem {
+                    nft_contract_id,
+                    token_id,
+                    offers_policy,
+                },
+            ),
+            |(nft_contract_id, token_id)| MarketEvent::ItemRemoved {
+                nft_contract_id,
+                token_id,
+            },
+        )
+    }
+
+    pub fn buy_item(
+        &self,
+        from: u64,
+        nft_contract_id: ActorId,
+        token_id: TokenId,
+        value: u128,
+    ) -> MarketRunResult<(ActorId, ContractId, TokenId)> {
+        RunResult::new(
+            self.0.send_with_value(
+                from,
+                MarketAction::BuyItem {
+                    nft_contract_id,
+                    token_id,
+                },
+                value,
+            ),
+            |(owner, nft_contract_id, token_id)| MarketEvent::ItemSold {
+                owner,
+                nft_contract_id,
+                token_id,
+            },
+        )
+    }
+
+    pub fn buy_items(
+        &self,
+        from: u64,
+        items: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        max_total_price: Price,
+        value: u128,
+    ) -> MarketRunResult<Vec<(ContractId, TokenId, Result<Price, MarketErr>)>> {
+        RunResult::new(
+            self.0.send_with_value(
+                from,
+                MarketAction::BuyItems {
+                    items,
+                    ft_contract_id,
+                    max_total_price,
+                },
+                value,
+            ),
+            MarketEvent::ItemsBought,
+        )
+    }
+
+    pub fn add_bundle(
+        &self,
+        from: u64,
+        tokens: Vec<(ContractId, TokenId)>,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> MarketRunResult<(BundleId, ActorId, Price)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::AddBundle {
+                    tokens,
+                    ft_contract_id,
+                    price,
+                },
+            ),
+            |(bundle_id, owner, price)| MarketEvent::BundleAdded {
+                bundle_id,
+                owner,
+                price,
+            },
+        )
+    }
+
+    pub fn remove_bundle(&self, from: u64, bundle_id: BundleId) -> MarketRunResult<BundleId> {
+        RunResult::new(
+            self.0.send(from, MarketAction::RemoveBundle { bundle_id }),
+            |bundle_id| MarketEvent::BundleRemoved { bundle_id },
+        )
+    }
+
+    pub fn buy_bundle(
+        &self,
+        from: u64,
+        bundle_id: BundleId,
+        value: u128,
+    ) -> MarketRunResult<(BundleId, ActorId, Price)> {
+        RunResult::new(
+            self.0
+                .send_with_value(from, MarketAction::BuyBundle { bundle_id }, value),
+            |(bundle_id, owner, price)| MarketEvent::BundleSold {
+                bundle_id,
+                owner,
+                price,
+            },
+        )
+    }
+
+    pub fn propose_swap(
+        &self,
+        from: u64,
+        counterparty: ActorId,
+        (offered, requested): (Vec<(ContractId, TokenId)>, Vec<(ContractId, TokenId)>),
+        ft_contract_id: Option<ContractId>,
+        payment: Price,
+        value: u128,
+    ) -> MarketRunResult<(SwapId, ActorId, ActorId)> {
+        RunResult::new(
+            self.0.send_with_value(
+                from,
+                MarketAction::ProposeSwap {
+                    counterparty,
+                    offered,
+                    requested,
+                    ft_contract_id,
+                    payment,
+                },
+                value,
+            ),
+            |(swap_id, proposer, counterparty)| MarketEvent::SwapProposed {
+                swap_id,
+                proposer,
+                counterparty,
+            },
+        )
+    }
+
+    pub fn accept_swap(&self, from: u64, swap_id: SwapId) -> MarketRunResult<SwapId> {
+        RunResult::new(
+            self.0.send(from, MarketAction::AcceptSwap { swap_id }),
+            |swap_id| MarketEvent::SwapCompleted { swap_id },
+        )
+    }
+
+    pub fn cancel_swap(&self, from: u64, swap_id: SwapId) -> MarketRunResult<SwapId> {
+        RunResult::new(
+            self.0.send(from, MarketAction::CancelSwap { swap_id }),
+            |swap_id| MarketEvent::SwapCancelled { swap_id },
+        )
+    }
+
+    pub fn add_offer(
+        &self,
+        from: ActorId,
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        value: u128,
+    ) -> MarketRunResult<(ContractId, Option<ContractId>, TokenId, Price)> {
+        self.add_offer_expiring(
+            from,
+            (nft_contract_id, token_id, ft_contract_id),
+            price,
+            None,
+            value,
+        )
+    }
+
+    pub fn add_offer_expiring(
+        &self,
+        from: ActorId,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
+        price: Price,
+        expires_at: Option<u64>,
+        value: u128,
+    ) -> MarketRunResult<(ContractId, Option<ContractId>, TokenId, Price)> {
+        RunResult::new(
+            self.0.send_with_value(
+                from.as_ref(),
+                MarketAction::AddOffer {
+                    nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    price,
+                    expires_at,
+                },
+                value,
+            ),
+            |(nft_contract_id, ft_contract_id, token_id, price)| MarketEvent::OfferAdded {
+                nft_contract_id,
+                ft_contract_id,
+                token_id,
+                price,
+            },
+        )
+    }
+
+    pub fn accept_offer(
+        &self,
+        from: ActorId,
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        buyer: ActorId,
+    ) -> MarketRunResult<(ContractId, TokenId, ActorId, Price)> {
+        RunResult::new(
+            self.0.send(
+                from.as_ref(),
+                MarketAction::AcceptOffer {
+                    nft_contract_id,
+                    token_id,
+                    ft_contract_id,
+                    price,
+                    buyer,
+                },
+            ),
+            |(nft_contract_id, token_id, new_owner, price)| MarketEvent::OfferAccepted {
+                nft_contract_id,
+                token_id,
+                new_owner,
+                price,
+            },
+        )
+    }
+
+    pub fn add_collection_offer(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+        value: u128,
+    ) -> MarketRunResult<(ContractId, Option<ContractId>, Price)> {
+        self.add_attribute_offer(
+            from,
+            (nft_contract_id, ft_contract_id),
+            price,
+            value,
+            vec![],
+        )
+    }
+
+    pub fn add_attribute_offer(
+        &self,
+        from: u64,
+        (nft_contract_id, ft_contract_id): (ContractId, Option<ContractId>),
+        price: Price,
+        value: u128,
+        attributes: Attributes,
+    ) -> MarketRunResult<(ContractId, Option<ContractId>, Price)> {
+        self.add_collection_offer_expiring(
+            from,
+            (nft_contract_id, ft_contract_id),
+            price,
+            (None, attributes),
+            value,
+        )
+    }
+
+    pub fn add_collection_offer_expiring(
+        &self,
+        from: u64,
+        (nft_contract_id, ft_contract_id): (ContractId, Option<ContractId>),
+        price: Price,
+        (expires_at, attributes): (Option<u64>, Attributes),
+        value: u128,
+    ) -> MarketRunResult<(ContractId, Option<ContractId>, Price)> {
+        RunResult::new(
+            self.0.send_with_value(
+                from,
+                MarketAction::AddCollectionOffer {
+                    nft_contract_id,
+                    ft_contract_id,
+                    price,
+                    expires_at,
+                    attributes,
+                },
+                value,
+            ),
+            |(nft_contract_id, ft_contract_id, price)| MarketEvent::CollectionOfferAdded {
+                nft_contract_id,
+                ft_contract_id,
+                price,
+            },
+        )
+    }
+
+    pub fn accept_collection_offer(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
+        price: Price,
+        buyer: ActorId,
+    ) -> MarketRunResult<(ContractId, TokenId, ActorId, Price)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::AcceptCollectionOffer {
+                    nft_contract_id,
+                    token_id,
+                    ft_contract_id,
+                    price,
+                    buyer,
+                },
+            ),
+            |(nft_contract_id, token_id, new_owner, price)| MarketEvent::OfferAccepted {
+                nft_contract_id,
+                token_id,
+                new_owner,
+                price,
+            },
+        )
+    }
+
+    pub fn withdraw_collection_offer(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        ft_contract_id: Option<ContractId>,
+        price: Price,
+    ) -> MarketRunResult<(ContractId, Option<ContractId>, Price)> {
         RunResult::new(
             self.0.send(
                 from,
-                MarketAction::AddMarketData {
+                MarketAction::WithdrawCollectionOffer {
                     nft_contract_id,
                     ft_contract_id,
-                    token_id,
                     price,
                 },
             ),
-            |(nft_contract_id, token_id, price)| MarketEvent::MarketDataAdded {
+            |(nft_contract_id, ft_contract_id, price)| MarketEvent::CollectionOfferWithdrawn {
                 nft_contract_id,
-                token_id,
+                ft_contract_id,
                 price,
             },
         )
     }
 
-    pub fn buy_item(
+    pub fn cleanup_expired_offers(
         &self,
         from: u64,
-        nft_contract_id: ActorId,
+        nft_contract_id: ContractId,
         token_id: TokenId,
-        value: u128,
-    ) -> MarketRunResult<(ActorId, ContractId, TokenId)> {
+    ) -> MarketRunResult<(ContractId, TokenId, u32)> {
         RunResult::new(
-            self.0.send_with_value(
+            self.0.send(
                 from,
-                MarketAction::BuyItem {
+                MarketAction::CleanupExpiredOffers {
                     nft_contract_id,
                     token_id,
                 },
-                value,
             ),
-            |(owner, nft_contract_id, token_id)| MarketEvent::ItemSold {
-                owner,
+            |(nft_contract_id, token_id, count)| MarketEvent::ExpiredOffersCleaned {
                 nft_contract_id,
                 token_id,
+                count,
             },
         )
     }
 
-    pub fn add_offer(
+    pub fn cleanup_expired_collection_offers(
         &self,
-        from: ActorId,
+        from: u64,
         nft_contract_id: ContractId,
-        token_id: TokenId,
-        ft_contract_id: Option<ContractId>,
+    ) -> MarketRunResult<(ContractId, u32)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::CleanupExpiredCollectionOffers { nft_contract_id },
+            ),
+            |(nft_contract_id, count)| MarketEvent::ExpiredCollectionOffersCleaned {
+                nft_contract_id,
+                count,
+            },
+        )
+    }
+
+    pub fn counter_offer(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
+        price: Price,
+        buyer: ActorId,
+        counter_price: Price,
+    ) -> MarketRunResult<(ContractId, TokenId, ActorId, Price, Price)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::CounterOffer {
+                    nft_contract_id,
+                    token_id,
+                    ft_contract_id,
+                    price,
+                    buyer,
+                    counter_price,
+                },
+            ),
+            |(nft_contract_id, token_id, buyer, price, counter_price)| {
+                MarketEvent::CounterOfferMade {
+                    nft_contract_id,
+                    token_id,
+                    buyer,
+                    price,
+                    counter_price,
+                }
+            },
+        )
+    }
+
+    pub fn accept_counter_offer(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
         price: Price,
         value: u128,
-    ) -> MarketRunResult<(ContractId, Option<ContractId>, TokenId, Price)> {
+    ) -> MarketRunResult<(ContractId, TokenId, ActorId, Price)> {
         RunResult::new(
             self.0.send_with_value(
-                from.as_ref(),
-                MarketAction::AddOffer {
+                from,
+                MarketAction::AcceptCounterOffer {
                     nft_contract_id,
-                    ft_contract_id,
                     token_id,
+                    ft_contract_id,
                     price,
                 },
                 value,
             ),
-            |(nft_contract_id, ft_contract_id, token_id, price)| MarketEvent::OfferAdded {
+            |(nft_contract_id, token_id, new_owner, price)| MarketEvent::OfferAccepted {
                 nft_contract_id,
-                ft_contract_id,
                 token_id,
+                new_owner,
                 price,
             },
         )
     }
 
-    pub fn accept_offer(
+    pub fn decline_counter_offer(
         &self,
-        from: ActorId,
-        nft_contract_id: ContractId,
-        token_id: TokenId,
-        ft_contract_id: Option<ContractId>,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
         price: Price,
     ) -> MarketRunResult<(ContractId, TokenId, ActorId, Price)> {
         RunResult::new(
             self.0.send(
-                from.as_ref(),
-                MarketAction::AcceptOffer {
+                from,
+                MarketAction::DeclineCounterOffer {
                     nft_contract_id,
                     token_id,
                     ft_contract_id,
                     price,
                 },
             ),
-            |(nft_contract_id, token_id, new_owner, price)| MarketEvent::OfferAccepted {
+            |(nft_contract_id, token_id, buyer, price)| MarketEvent::CounterOfferDeclined {
                 nft_contract_id,
                 token_id,
-                new_owner,
+                buyer,
                 price,
             },
         )
@@ -203,6 +816,29 @@ impl<'a> Market<'a> {
         min_price: u128,
         bid_period: u64,
         duration: u64,
+    ) -> MarketRunResult<(ContractId, TokenId, Price)> {
+        self.create_auction_with_prices(
+            from,
+            (nft_contract_id, token_id, ft_contract_id),
+            min_price,
+            (None, None),
+            BidIncrement::default(),
+            false,
+            bid_period,
+            duration,
+        )
+    }
+
+    pub fn create_auction_with_prices(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
+        min_price: u128,
+        (reserve_price_commitment, buy_now_price): (Option<[u8; 32]>, Option<u128>),
+        bid_increment: BidIncrement,
+        auto_settlement: bool,
+        bid_period: u64,
+        duration: u64,
     ) -> MarketRunResult<(ContractId, TokenId, Price)> {
         RunResult::new(
             self.0.send(
@@ -214,6 +850,11 @@ impl<'a> Market<'a> {
                     min_price,
                     bid_period,
                     duration,
+                    max_extension: MAX_EXTENSION,
+                    reserve_price_commitment,
+                    buy_now_price,
+                    bid_increment,
+                    auto_settlement,
                 },
             ),
             |(nft_contract_id, token_id, price)| MarketEvent::AuctionCreated {
@@ -224,6 +865,140 @@ impl<'a> Market<'a> {
         )
     }
 
+    pub fn create_dutch_auction(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
+        (start_price, floor_price): (Price, Price),
+        decay: PriceDecay,
+        duration: u64,
+    ) -> MarketRunResult<(ContractId, TokenId, Price, Price)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::CreateDutchAuction {
+                    nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    start_price,
+                    floor_price,
+                    decay,
+                    duration,
+                },
+            ),
+            |(nft_contract_id, token_id, start_price, floor_price)| {
+                MarketEvent::DutchAuctionCreated {
+                    nft_contract_id,
+                    token_id,
+                    start_price,
+                    floor_price,
+                }
+            },
+        )
+    }
+
+    pub fn create_sealed_auction(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id, ft_contract_id): (ContractId, TokenId, Option<ContractId>),
+        min_price: Price,
+        pricing: SealedBidPricing,
+        (commit_duration, reveal_duration): (u64, u64),
+    ) -> MarketRunResult<(ContractId, TokenId, Price)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::CreateSealedAuction {
+                    nft_contract_id,
+                    ft_contract_id,
+                    token_id,
+                    min_price,
+                    pricing,
+                    commit_duration,
+                    reveal_duration,
+                },
+            ),
+            |(nft_contract_id, token_id, min_price)| MarketEvent::SealedAuctionCreated {
+                nft_contract_id,
+                token_id,
+                min_price,
+            },
+        )
+    }
+
+    pub fn commit_bid(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        commitment: [u8; 32],
+        deposit: Price,
+        value: u128,
+    ) -> MarketRunResult<(ContractId, TokenId, ActorId)> {
+        RunResult::new(
+            self.0.send_with_value(
+                from,
+                MarketAction::CommitBid {
+                    nft_contract_id,
+                    token_id,
+                    commitment,
+                    deposit,
+                },
+                value,
+            ),
+            |(nft_contract_id, token_id, account)| MarketEvent::SealedBidCommitted {
+                nft_contract_id,
+                token_id,
+                account,
+            },
+        )
+    }
+
+    pub fn reveal_bid(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+        price: Price,
+        salt: [u8; 32],
+    ) -> MarketRunResult<(ContractId, TokenId, ActorId, Price)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::RevealBid {
+                    nft_contract_id,
+                    token_id,
+                    price,
+                    salt,
+                },
+            ),
+            |(nft_contract_id, token_id, account, price)| MarketEvent::SealedBidRevealed {
+                nft_contract_id,
+                token_id,
+                account,
+                price,
+            },
+        )
+    }
+
+    pub fn settle_sealed_auction(
+        &self,
+        from: u64,
+        nft_contract_id: ContractId,
+        token_id: TokenId,
+    ) -> MarketRunResult<MarketEvent> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::SettleSealedAuction {
+                    nft_contract_id,
+                    token_id,
+                },
+            ),
+            |market_event| market_event,
+        )
+    }
+
     pub fn add_bid(
         &self,
         from: u64,
@@ -250,6 +1025,99 @@ impl<'a> Market<'a> {
         )
     }
 
+    pub fn add_bid_extending(
+        &self,
+        from: u64,
+        nft_contract_id: ActorId,
+        token_id: TokenId,
+        price: u128,
+        value: u128,
+    ) -> MarketRunResult<(ContractId, TokenId, Price, u64)> {
+        RunResult::new(
+            self.0.send_with_value(
+                from,
+                MarketAction::AddBid {
+                    nft_contract_id,
+                    token_id,
+                    price,
+                },
+                value,
+            ),
+            |(nft_contract_id, token_id, price, new_end)| MarketEvent::AuctionExtended {
+                nft_contract_id,
+                token_id,
+                price,
+                new_end,
+            },
+        )
+    }
+
+    pub fn buy_now(
+        &self,
+        from: u64,
+        nft_contract_id: ActorId,
+        token_id: TokenId,
+        value: u128,
+    ) -> MarketRunResult<MarketEvent> {
+        RunResult::new(
+            self.0.send_with_value(
+                from,
+                MarketAction::BuyNow {
+                    nft_contract_id,
+                    token_id,
+                },
+                value,
+            ),
+            |market_event| market_event,
+        )
+    }
+
+    pub fn reveal_reserve_price(
+        &self,
+        from: u64,
+        (nft_contract_id, token_id): (ContractId, TokenId),
+        reserve_price: Price,
+        salt: [u8; 32],
+    ) -> MarketRunResult<(ContractId, TokenId, Price)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::RevealReservePrice {
+                    nft_contract_id,
+                    token_id,
+                    reserve_price,
+                    salt,
+                },
+            ),
+            |(nft_contract_id, token_id, reserve_price)| MarketEvent::ReservePriceRevealed {
+                nft_contract_id,
+                token_id,
+                reserve_price,
+            },
+        )
+    }
+
+    pub fn cancel_auction(
+        &self,
+        from: u64,
+        nft_contract_id: ActorId,
+        token_id: TokenId,
+    ) -> MarketRunResult<(ContractId, TokenId)> {
+        RunResult::new(
+            self.0.send(
+                from,
+                MarketAction::CancelAuction {
+                    nft_contract_id,
+                    token_id,
+                },
+            ),
+            |(nft_contract_id, token_id)| MarketEvent::AuctionCancelled {
+                nft_contract_id,
+                token_id,
+            },
+        )
+    }
+
     pub fn settle_auction(
         &self,
         from: u64,
@@ -1,7 +1,7 @@
 use super::{prelude::*, MetaStateReply};
 use gear_lib::non_fungible_token::{io::*, token::Token};
 use gstd::ActorId;
-use gtest::{Log, Program as InnerProgram, System};
+use gtest::{Log, Program as InnerProgram, System, WasmProgram};
 use market_io::*;
 use nft_io::{Collection, Constraints, InitNFT, NFTAction, NFTEvent};
 
@@ -85,16 +85,136 @@ impl<'a> NonFungibleToken<'a> {
             }))));
     }
 
+    pub fn transfer(&self, transaction_id: u64, from: u64, to: u64, token_id: TokenId) {
+        assert!(self
+            .0
+            .send(
+                from,
+                NFTAction::Transfer {
+                    transaction_id,
+                    to: to.into(),
+                    token_id
+                }
+            )
+            .contains(&Log::builder().payload(NFTEvent::Transfer(NFTTransfer {
+                from: from.into(),
+                to: to.into(),
+                token_id,
+            }))));
+    }
+
+    pub fn meta_state(&self) -> NonFungibleTokenMetaState {
+        NonFungibleTokenMetaState(&self.0)
+    }
+}
+
+/// The NFT contract with the single token that has the attributes.
+///
+/// It answers [`NFTAttributesAction`] and the NFT actions
+/// that the marketplace sends to accept a collection offer.
+pub struct AttributeToken<'a>(InnerProgram<'a>);
+
+impl Program for AttributeToken<'_> {
+    fn inner_program(&self) -> &InnerProgram {
+        &self.0
+    }
+}
+
+impl<'a> AttributeToken<'a> {
+    pub fn initialize(system: &'a System, owner: u64, attributes: Attributes) -> Self {
+        let program = InnerProgram::mock(
+            system,
+            AttributeTokenMock {
+                owner: owner.into(),
+                attributes,
+            },
+        );
+
+        assert!(!program.send_bytes(ADMIN, b"").main_failed());
+
+        Self(program)
+    }
+
     pub fn meta_state(&self) -> NonFungibleTokenMetaState {
         NonFungibleTokenMetaState(&self.0)
     }
 }
 
+#[derive(Debug)]
+struct AttributeTokenMock {
+    owner: ActorId,
+    attributes: Attributes,
+}
+
+impl WasmProgram for AttributeTokenMock {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        if let Ok(NFTAttributesAction::TokenAttributes { token_id }) =
+            NFTAttributesAction::decode(&mut payload.as_ref())
+        {
+            let reply = TokenAttributes {
+                token_id,
+                attributes: self.attributes.clone(),
+            };
+
+            return Ok(Some(reply.encode()));
+        }
+
+        let reply = match NFTAction::decode(&mut payload.as_ref())
+            .map_err(|_| "Unexpected invalid `NFTAction` data")?
+        {
+            NFTAction::Owner { token_id } => NFTEvent::Owner {
+                owner: self.owner,
+                token_id,
+            },
+            NFTAction::NFTPayout { owner, amount } => NFTEvent::NFTPayout([(owner, amount)].into()),
+            NFTAction::Transfer { to, token_id, .. } => {
+                let from = mem::replace(&mut self.owner, to);
+
+                NFTEvent::Transfer(NFTTransfer { from, to, token_id })
+            }
+            _ => return Err("Unsupported `NFTAction`"),
+        };
+
+        Ok(Some(reply.encode()))
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(self.owner.encode())
+    }
+}
+
 pub struct NonFungibleTokenMetaState<'a>(&'a InnerProgram<'a>);
 
 impl NonFungibleTokenMetaState<'_> {
     pub fn owner_id(self, token_id: u64) -> MetaStateReply<ActorId> {
-        MetaStateReply(self.token(token_id).0.owner_id)
+        let result = self.0.send(
+            ADMIN,
+            NFTAction::Owner {
+                token_id: token_id.into(),
+            },
+        );
+        let owner = result
+            .log()
+            .iter()
+            .find_map(|log| match NFTEvent::decode(&mut log.payload()) {
+                Ok(NFTEvent::Owner { owner, .. }) => Some(owner),
+                _ => None,
+            })
+            .expect("Unexpected invalid reply");
+
+        MetaStateReply(owner)
     }
 
     pub fn token(self, _token_id: u64) -> MetaStateReply<Token> {
@@ -9,11 +9,12 @@ pub const USERS_FUND: u128 = 5_000;
 pub const SELLER: &str = "//Markus";
 pub const BUYER: &str = "//Jim";
 pub const TREASURY: &str = "//Treasury";
-pub const TREASURY_FEE: u16 = 3;
+pub const TREASURY_FEE: u16 = 300;
 pub const TOKEN_ID: u128 = 0;
 pub const NFT_PRICE: u128 = 100_000;
 pub const BID_PERIOD: u64 = 3_600_000;
 pub const DURATION: u64 = 86_400_000;
+pub const MAX_EXTENSION: u64 = 2 * BID_PERIOD;
 
 static mut API: Option<GearApi> = None;
 
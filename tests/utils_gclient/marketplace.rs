@@ -1,11 +1,11 @@
 use super::common;
 use gclient::{EventListener, EventProcessor, GearApi};
 use gstd::{prelude::*, ActorId};
-use market_io::{InitMarket, Market, MarketAction, MarketErr, MarketEvent, TokenId};
+use market_io::{BidIncrement, InitMarket, Market, MarketAction, MarketErr, MarketEvent, TokenId};
 
 const MARKETPLACE_WASM_PATH: &str =
     "./target/wasm32-unknown-unknown/debug/nft_marketplace.opt.wasm";
-pub const TREASURY_FEE: u16 = 3;
+pub const TREASURY_FEE: u16 = 300;
 
 pub async fn init(api: &GearApi, admin: &ActorId, treasury: &ActorId) -> gclient::Result<ActorId> {
     let mut listener = api.subscribe().await?;
@@ -125,6 +125,7 @@ pub async fn add_market_data(
             ft_contract_id: ft_contract,
             token_id,
             price,
+            expires_at: None,
         },
         0,
     )
@@ -201,6 +202,11 @@ pub async fn create_auction(
             min_price,
             bid_period,
             duration,
+            max_extension: common::MAX_EXTENSION,
+            reserve_price_commitment: None,
+            buy_now_price: None,
+            bid_increment: BidIncrement::default(),
+            auto_settlement: false,
         },
         0,
     )
@@ -310,6 +316,7 @@ pub async fn add_offer(
             ft_contract_id: ft_contract,
             token_id,
             price,
+            expires_at: None,
         },
         value,
     )
@@ -375,6 +382,7 @@ pub async fn accept_offer(
     ft_contract: Option<ActorId>,
     token_id: TokenId,
     price: u128,
+    buyer: ActorId,
     should_fail: bool,
 ) -> gclient::Result<()> {
     let reply = send_message(
@@ -386,6 +394,7 @@ pub async fn accept_offer(
             ft_contract_id: ft_contract,
             token_id,
             price,
+            buyer,
         },
         0,
     )